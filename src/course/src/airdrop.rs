@@ -0,0 +1,217 @@
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::RpcClient,
+    rpc_request::RpcError,
+};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signature,
+    transaction::TransactionError,
+};
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AirdropError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    #[error("faucet is rate limiting airdrops, gave up after {attempts} attempts: {source}")]
+    RateLimited {
+        attempts: u32,
+        source: ClientError,
+    },
+
+    #[error("airdrop {0} was not confirmed in time")]
+    Timeout(Signature),
+
+    #[error("airdrop {signature} failed: {err}")]
+    Failed {
+        signature: Signature,
+        err: TransactionError,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AirdropConfig {
+    /// Largest amount requested at once. Devnet refuses more than 2 SOL per
+    /// request, the test validator faucet is more generous.
+    pub max_airdrop: u64,
+    pub poll_interval: Duration,
+    pub confirm_timeout: Duration,
+    /// Rate limited requests retried before giving up.
+    pub max_rate_limit_retries: u32,
+    /// First wait after a rate limited request, doubled after each retry.
+    pub rate_limit_backoff: Duration,
+}
+
+impl Default for AirdropConfig {
+    fn default() -> Self {
+        Self {
+            max_airdrop: LAMPORTS_PER_SOL,
+            poll_interval: Duration::from_millis(500),
+            confirm_timeout: Duration::from_secs(60),
+            max_rate_limit_retries: 4,
+            rate_limit_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Airdrops to `pubkey` until it holds at least `min_lamports`, waiting for
+/// each airdrop to confirm. Returns the final balance.
+pub fn ensure_funded(
+    client: &RpcClient,
+    pubkey: &Pubkey,
+    min_lamports: u64,
+) -> Result<u64, AirdropError> {
+    ensure_funded_with_config(
+        client,
+        pubkey,
+        min_lamports,
+        AirdropConfig::default(),
+    )
+}
+
+pub fn ensure_funded_with_config(
+    client: &RpcClient,
+    pubkey: &Pubkey,
+    min_lamports: u64,
+    config: AirdropConfig,
+) -> Result<u64, AirdropError> {
+    let mut balance = client.get_balance(pubkey)?;
+
+    while balance < min_lamports {
+        let lamports = (min_lamports - balance).min(config.max_airdrop.max(1));
+        let signature = request_airdrop(client, pubkey, lamports, &config)?;
+        confirm_airdrop(client, &signature, &config)?;
+
+        balance = client.get_balance(pubkey)?;
+    }
+
+    Ok(balance)
+}
+
+fn request_airdrop(
+    client: &RpcClient,
+    pubkey: &Pubkey,
+    lamports: u64,
+    config: &AirdropConfig,
+) -> Result<Signature, AirdropError> {
+    let mut attempts = 0;
+    let mut backoff = config.rate_limit_backoff;
+
+    loop {
+        attempts += 1;
+        match client.request_airdrop(pubkey, lamports) {
+            Ok(signature) => return Ok(signature),
+            Err(e) if is_rate_limited(&e) => {
+                if attempts > config.max_rate_limit_retries {
+                    return Err(AirdropError::RateLimited {
+                        attempts,
+                        source: e,
+                    });
+                }
+                sleep(backoff);
+                backoff *= 2;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn confirm_airdrop(
+    client: &RpcClient,
+    signature: &Signature,
+    config: &AirdropConfig,
+) -> Result<(), AirdropError> {
+    let started = Instant::now();
+
+    while started.elapsed() < config.confirm_timeout {
+        let status =
+            client.get_signature_statuses(&[*signature])?.value.remove(0);
+
+        if let Some(status) = status {
+            if let Some(err) = status.err {
+                return Err(AirdropError::Failed {
+                    signature: *signature,
+                    err,
+                });
+            }
+            if status.satisfies_commitment(client.commitment()) {
+                return Ok(());
+            }
+        }
+
+        sleep(config.poll_interval);
+    }
+
+    Err(AirdropError::Timeout(*signature))
+}
+
+/// Faucets answer with HTTP 429 or with an RPC error mentioning the limit.
+fn is_rate_limited(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Reqwest(e) => {
+            e.status().is_some_and(|status| status.as_u16() == 429)
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code,
+            message,
+            ..
+        }) => {
+            *code == 429
+                || message.contains("429")
+                || message.to_lowercase().contains("limit")
+        }
+        ClientErrorKind::RpcError(RpcError::ForUser(message)) => {
+            message.contains("429")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::rpc_request::RpcResponseErrorData;
+
+    fn response_error(code: i64, message: &str) -> ClientError {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code,
+            message: message.to_string(),
+            data: RpcResponseErrorData::Empty,
+        })
+        .into()
+    }
+
+    #[test]
+    fn detects_rate_limits() {
+        assert!(is_rate_limited(&response_error(429, "Too Many Requests")));
+        assert!(is_rate_limited(&response_error(
+            -32603,
+            "airdrop request limit reached"
+        )));
+        assert!(is_rate_limited(
+            &ClientErrorKind::RpcError(RpcError::ForUser(
+                "HTTP status client error (429 Too Many Requests)".to_string()
+            ))
+            .into()
+        ));
+
+        assert!(!is_rate_limited(&response_error(-32602, "invalid pubkey")));
+        assert!(!is_rate_limited(
+            &ClientErrorKind::Custom("timeout".to_string()).into()
+        ));
+    }
+
+    #[test]
+    fn funded_accounts_are_not_topped_up() {
+        // The mock reports a balance of 50 lamports and fails airdrops.
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let balance =
+            ensure_funded(&client, &Pubkey::new_unique(), 50).unwrap();
+        assert_eq!(balance, 50);
+    }
+}
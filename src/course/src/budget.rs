@@ -0,0 +1,423 @@
+use solana_client::{
+    client_error::{ClientErrorKind, Result as ClientResult},
+    rpc_client::RpcClient,
+    rpc_config::RpcSimulateTransactionConfig,
+};
+use solana_sdk::{
+    address_lookup_table_account::AddressLookupTableAccount,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::signers::Signers,
+    transaction::VersionedTransaction,
+};
+
+/// Most compute units a single transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+/// `getRecentPrioritizationFees` accepts at most this many accounts.
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ComputeBudgetConfig {
+    /// Extra compute units requested on top of the simulated usage, in
+    /// percent.
+    pub unit_margin_percent: u32,
+    /// Limit used when the simulation reports no usage.
+    pub fallback_unit_limit: u32,
+    /// Percentile of the recent prioritization fees to pay, 0 to 100.
+    pub fee_percentile: u8,
+    /// Most lamports paid in priority fees per transaction. The unit price
+    /// is lowered until the fee fits, 0 disables priority fees.
+    pub max_priority_fee: u64,
+}
+
+impl Default for ComputeBudgetConfig {
+    fn default() -> Self {
+        Self {
+            unit_margin_percent: 10,
+            fallback_unit_limit: 200_000,
+            fee_percentile: 50,
+            max_priority_fee: 100_000,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub unit_limit: u32,
+    /// Price per compute unit in micro-lamports.
+    pub unit_price: u64,
+}
+
+impl ComputeBudget {
+    pub fn instructions(&self) -> Vec<Instruction> {
+        vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(self.unit_limit),
+            ComputeBudgetInstruction::set_compute_unit_price(self.unit_price),
+        ]
+    }
+
+    /// Lamports paid on top of the signature fees.
+    pub fn priority_fee(&self) -> u64 {
+        priority_fee(self.unit_limit, self.unit_price)
+    }
+}
+
+/// Measures the compute units `instructions` use by simulating them and
+/// prices them from the recent fees paid for their writable accounts.
+///
+/// Fails with the transaction error if the simulation fails, as sending
+/// would fail the same way.
+pub fn estimate_compute_budget(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    config: &ComputeBudgetConfig,
+) -> ClientResult<ComputeBudget> {
+    estimate_versioned_compute_budget(client, instructions, payer, &[], config)
+}
+
+/// Like [`estimate_compute_budget`], simulating a v0 message that resolves
+/// accounts through `lookup_tables`.
+pub fn estimate_versioned_compute_budget(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+    config: &ComputeBudgetConfig,
+) -> ClientResult<ComputeBudget> {
+    // Simulate with the budget instructions in place so their own units are
+    // counted too.
+    let budget = ComputeBudget {
+        unit_limit: MAX_COMPUTE_UNIT_LIMIT,
+        unit_price: 0,
+    };
+    let instructions = [budget.instructions(), instructions.to_vec()].concat();
+    let message =
+        compile_message(&instructions, payer, lookup_tables, Hash::default())?;
+    let transaction = VersionedTransaction {
+        signatures: vec![
+            Signature::default();
+            usize::from(message.header().num_required_signatures)
+        ],
+        message,
+    };
+    let result = client.simulate_transaction_with_config(
+        &transaction,
+        RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            ..RpcSimulateTransactionConfig::default()
+        },
+    )?;
+
+    if let Some(err) = result.value.err {
+        return Err(ClientErrorKind::TransactionError(err).into());
+    }
+    let unit_limit = match result.value.units_consumed {
+        Some(units) if units > 0 => {
+            let margin = units * u64::from(config.unit_margin_percent) / 100;
+            (units + margin).min(u64::from(MAX_COMPUTE_UNIT_LIMIT)) as u32
+        }
+        _ => config.fallback_unit_limit,
+    };
+
+    let unit_price = if config.max_priority_fee == 0 {
+        0
+    } else {
+        let mut writable =
+            writable_accounts(&transaction.message, lookup_tables);
+        writable.truncate(MAX_FEE_ACCOUNTS);
+        let fees = client
+            .get_recent_prioritization_fees(&writable)?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        percentile(fees, config.fee_percentile)
+            .min(max_unit_price(unit_limit, config.max_priority_fee))
+    };
+
+    Ok(ComputeBudget {
+        unit_limit,
+        unit_price,
+    })
+}
+
+/// Prepends compute budget instructions sized for `instructions`.
+pub fn with_compute_budget(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    config: &ComputeBudgetConfig,
+) -> ClientResult<Vec<Instruction>> {
+    let budget = estimate_compute_budget(client, instructions, payer, config)?;
+
+    Ok([budget.instructions(), instructions.to_vec()].concat())
+}
+
+/// Programs of the top level instructions of a transaction sent with a
+/// compute budget, in the order they run.
+pub fn budgeted_program_ids(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let budget = ComputeBudget {
+        unit_limit: 0,
+        unit_price: 0,
+    };
+    budget
+        .instructions()
+        .iter()
+        .chain(instructions)
+        .map(|instruction| instruction.program_id)
+        .collect()
+}
+
+/// Signs and sends `instructions` with a compute budget, waiting for
+/// confirmation.
+pub fn send_with_compute_budget<T: Signers + ?Sized>(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &T,
+    config: &ComputeBudgetConfig,
+) -> ClientResult<Signature> {
+    send_versioned_with_compute_budget(
+        client,
+        instructions,
+        payer,
+        signers,
+        &[],
+        config,
+    )
+}
+
+/// Like [`send_with_compute_budget`], sending a v0 transaction that resolves
+/// accounts through `lookup_tables`, or a legacy one when there are none.
+pub fn send_versioned_with_compute_budget<T: Signers + ?Sized>(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &T,
+    lookup_tables: &[AddressLookupTableAccount],
+    config: &ComputeBudgetConfig,
+) -> ClientResult<Signature> {
+    let budget = estimate_versioned_compute_budget(
+        client,
+        instructions,
+        payer,
+        lookup_tables,
+        config,
+    )?;
+    let instructions = [budget.instructions(), instructions.to_vec()].concat();
+
+    let latest_blockhash = client.get_latest_blockhash()?;
+    let message =
+        compile_message(&instructions, payer, lookup_tables, latest_blockhash)?;
+    let transaction = VersionedTransaction::try_new(message, signers)?;
+
+    client.send_and_confirm_transaction(&transaction)
+}
+
+/// Compiles a v0 message using `lookup_tables`, or a legacy message when
+/// there are none.
+pub fn compile_message(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> ClientResult<VersionedMessage> {
+    if lookup_tables.is_empty() {
+        return Ok(VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(payer),
+            &blockhash,
+        )));
+    }
+
+    v0::Message::try_compile(payer, instructions, lookup_tables, blockhash)
+        .map(VersionedMessage::V0)
+        .map_err(|e| ClientErrorKind::Custom(e.to_string()).into())
+}
+
+/// Writable accounts of `message`, including the ones loaded from lookup
+/// tables.
+pub(crate) fn writable_accounts(
+    message: &VersionedMessage,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Vec<Pubkey> {
+    let mut writable = message
+        .static_account_keys()
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| message.is_maybe_writable(*i))
+        .map(|(_, key)| *key)
+        .collect::<Vec<_>>();
+
+    for lookup in message.address_table_lookups().unwrap_or_default() {
+        let Some(table) =
+            lookup_tables.iter().find(|table| table.key == lookup.account_key)
+        else {
+            continue;
+        };
+        writable.extend(
+            lookup
+                .writable_indexes
+                .iter()
+                .filter_map(|i| table.addresses.get(usize::from(*i))),
+        );
+    }
+
+    writable
+}
+
+fn priority_fee(unit_limit: u32, unit_price: u64) -> u64 {
+    let micro_lamports = u128::from(unit_limit) * u128::from(unit_price);
+    micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT) as u64
+}
+
+/// Highest unit price whose fee at `unit_limit` stays within `max_fee`.
+fn max_unit_price(unit_limit: u32, max_fee: u64) -> u64 {
+    if unit_limit == 0 {
+        return 0;
+    }
+    let micro_lamports = u128::from(max_fee) * MICRO_LAMPORTS_PER_LAMPORT;
+    (micro_lamports / u128::from(unit_limit)).min(u128::from(u64::MAX)) as u64
+}
+
+fn percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let i = (fees.len() - 1) * usize::from(percentile.min(100)) / 100;
+    fees[i]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_client::rpc_request::RpcRequest;
+    use solana_sdk::{
+        instruction::InstructionError, system_instruction,
+        transaction::TransactionError,
+    };
+    use std::collections::HashMap;
+
+    fn simulation(
+        err: Option<TransactionError>,
+        units: Option<u64>,
+    ) -> RpcClient {
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::SimulateTransaction,
+            json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "err": err,
+                    "logs": [],
+                    "accounts": null,
+                    "unitsConsumed": units,
+                    "returnData": null,
+                },
+            }),
+        );
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+    }
+
+    fn transfer() -> (Pubkey, Vec<Instruction>) {
+        let payer = Pubkey::new_unique();
+        let ix = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+        (payer, vec![ix])
+    }
+
+    #[test]
+    fn percentile_picks_from_sorted_fees() {
+        assert_eq!(percentile(vec![], 50), 0);
+        assert_eq!(percentile(vec![30, 10, 20], 0), 10);
+        assert_eq!(percentile(vec![30, 10, 20], 50), 20);
+        assert_eq!(percentile(vec![30, 10, 20], 100), 30);
+        assert_eq!(percentile(vec![40, 10, 30, 20], 75), 30);
+        // Out of range percentiles are clamped to the highest fee.
+        assert_eq!(percentile(vec![30, 10, 20], 255), 30);
+    }
+
+    #[test]
+    fn max_unit_price_keeps_fee_within_cap() {
+        assert_eq!(max_unit_price(0, 100_000), 0);
+        assert_eq!(max_unit_price(200_000, 0), 0);
+        assert_eq!(max_unit_price(200_000, 100_000), 500_000);
+        // Rounded down so the fee never exceeds the cap.
+        assert_eq!(max_unit_price(300_000, 1), 3);
+        assert!(priority_fee(300_000, 3) <= 1);
+        assert_eq!(max_unit_price(1, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn priority_fee_rounds_up() {
+        assert_eq!(priority_fee(200_000, 0), 0);
+        assert_eq!(priority_fee(200_000, 500_000), 100_000);
+        assert_eq!(priority_fee(1, 1), 1);
+        assert!(priority_fee(MAX_COMPUTE_UNIT_LIMIT, u64::MAX) > 0);
+    }
+
+    #[test]
+    fn estimate_adds_margin_and_caps_price() {
+        let client = simulation(None, Some(1_000));
+        let (payer, instructions) = transfer();
+        let config = ComputeBudgetConfig {
+            max_priority_fee: 5,
+            ..ComputeBudgetConfig::default()
+        };
+
+        let budget =
+            estimate_compute_budget(&client, &instructions, &payer, &config)
+                .unwrap();
+
+        assert_eq!(budget.unit_limit, 1_100);
+        // The mock's recent fee of 10_000 is lowered to fit 5 lamports.
+        assert_eq!(budget.unit_price, 4_545);
+        assert!(budget.priority_fee() <= 5);
+    }
+
+    #[test]
+    fn estimate_falls_back_without_reported_usage() {
+        let client = simulation(None, None);
+        let (payer, instructions) = transfer();
+        let config = ComputeBudgetConfig {
+            max_priority_fee: 0,
+            ..ComputeBudgetConfig::default()
+        };
+
+        let budget =
+            estimate_compute_budget(&client, &instructions, &payer, &config)
+                .unwrap();
+
+        assert_eq!(
+            budget,
+            ComputeBudget {
+                unit_limit: config.fallback_unit_limit,
+                unit_price: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn estimate_reports_failed_simulation() {
+        let err =
+            TransactionError::InstructionError(2, InstructionError::Custom(1));
+        let client = simulation(Some(err.clone()), Some(500));
+        let (payer, instructions) = transfer();
+
+        let result = estimate_compute_budget(
+            &client,
+            &instructions,
+            &payer,
+            &ComputeBudgetConfig::default(),
+        );
+
+        assert_eq!(result.unwrap_err().get_transaction_error(), Some(err));
+    }
+}
@@ -0,0 +1,497 @@
+use crate::{
+    keypair::{self, KeypairError},
+    watch::websocket_url,
+    STUDENT_INTRO_PROGRAM_ID,
+};
+use serde::Deserialize;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    signer::keypair::Keypair,
+};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// Profile used when the config file does not name one.
+pub const DEFAULT_PROFILE: &str = "devnet";
+/// Program id name the student intro program is stored under.
+pub const STUDENT_INTRO_PROGRAM: &str = "student_intro";
+/// Program id name the mint-enabled student intro program of module 4 is
+/// stored under. It has no default, each profile sets its own deployment.
+pub const STUDENT_INTRO_MINT_PROGRAM: &str = "student_intro_mint";
+/// Program id name the hello world program is stored under.
+pub const HELLO_WORLD_PROGRAM: &str = "hello_world";
+const HELLO_WORLD_PROGRAM_ID: &str =
+    "9whiGXV1XPWdk1BaNTeheey9xuJk37U4amvqDnX1gpcW";
+const EXPLORER_URL: &str = "https://explorer.solana.com";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read config file {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid config file {path}: {source}")]
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("unknown profile `{0}`")]
+    UnknownProfile(String),
+
+    #[error("profile `{profile}` has an invalid commitment `{value}`")]
+    InvalidCommitment {
+        profile: String,
+        value: String,
+    },
+
+    #[error("profile `{profile}` has an invalid {name} program id `{value}`")]
+    InvalidProgramId {
+        profile: String,
+        name: String,
+        value: String,
+    },
+
+    #[error("profile `{profile}` has an unknown cluster `{value}`")]
+    InvalidCluster {
+        profile: String,
+        value: String,
+    },
+}
+
+/// Contents of the config file.
+///
+/// ```toml
+/// active = "localnet"
+///
+/// [profiles.localnet]
+/// rpc_url = "http://127.0.0.1:8899"
+/// commitment = "processed"
+/// keypair = "~/.config/solana/localnet.json"
+///
+/// [profiles.localnet.program_ids]
+/// student_intro = "HdE95RSVsdb315jfJtaykXhXY478h53X6okDupVfY9yf"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    /// Profile used unless another one is asked for.
+    pub active: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// A profile as written in the config file.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProfileConfig {
+    pub rpc_url: String,
+    /// Derived from `rpc_url` when omitted.
+    pub ws_url: Option<String>,
+    /// Defaults to `confirmed`.
+    pub commitment: Option<String>,
+    /// Anything `--keypair` accepts, defaults to `PRIVATE_KEY` or the Solana
+    /// CLI keypair.
+    pub keypair: Option<String>,
+    /// `mainnet-beta`, `testnet`, `devnet` or `custom`. Detected from
+    /// `rpc_url` when omitted, which only recognizes the public endpoints.
+    pub cluster: Option<String>,
+    #[serde(default)]
+    pub program_ids: BTreeMap<String, String>,
+}
+
+/// A profile ready to use.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    pub name: String,
+    pub rpc_url: String,
+    pub ws_url: String,
+    pub commitment: CommitmentConfig,
+    pub keypair: Option<String>,
+    pub cluster: Cluster,
+    pub program_ids: BTreeMap<String, Pubkey>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Cluster {
+    MainnetBeta,
+    Testnet,
+    Devnet,
+    /// Any other cluster, such as a local test validator, reached at this
+    /// RPC URL.
+    Custom(String),
+}
+
+impl Cluster {
+    /// Recognizes the public endpoints, anything else is custom.
+    pub fn from_rpc_url(rpc_url: &str) -> Self {
+        let host = rpc_url
+            .split_once("://")
+            .map_or(rpc_url, |(_, rest)| rest)
+            .split(['/', ':'])
+            .next()
+            .unwrap_or_default();
+
+        match host {
+            "api.mainnet-beta.solana.com" => Cluster::MainnetBeta,
+            "api.testnet.solana.com" => Cluster::Testnet,
+            "api.devnet.solana.com" => Cluster::Devnet,
+            _ => Cluster::Custom(rpc_url.to_string()),
+        }
+    }
+
+    /// Whether the cluster runs a faucet. Custom clusters are assumed to be
+    /// a local test validator, which does.
+    pub fn supports_airdrops(&self) -> bool {
+        !matches!(self, Cluster::MainnetBeta)
+    }
+
+    pub fn explorer_transaction_url(&self, signature: &Signature) -> String {
+        self.explorer_url(&format!("tx/{signature}"))
+    }
+
+    pub fn explorer_address_url(&self, address: &Pubkey) -> String {
+        self.explorer_url(&format!("address/{address}"))
+    }
+
+    fn explorer_url(&self, path: &str) -> String {
+        match self {
+            Cluster::MainnetBeta => format!("{EXPLORER_URL}/{path}"),
+            Cluster::Testnet => {
+                format!("{EXPLORER_URL}/{path}?cluster=testnet")
+            }
+            Cluster::Devnet => format!("{EXPLORER_URL}/{path}?cluster=devnet"),
+            Cluster::Custom(rpc_url) => format!(
+                "{EXPLORER_URL}/{path}?cluster=custom&customUrl={}",
+                percent_encode(rpc_url)
+            ),
+        }
+    }
+}
+
+impl Config {
+    /// The built in profiles, overridden by the ones in the file at `path`.
+    /// A missing file is not an error.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let mut config = Config::builtin();
+
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(config)
+            }
+            Err(source) => {
+                return Err(ConfigError::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        let file: Config =
+            toml::from_str(&contents).map_err(|source| ConfigError::Toml {
+                path: path.to_path_buf(),
+                source,
+            })?;
+
+        if file.active.is_some() {
+            config.active = file.active;
+        }
+        config.profiles.extend(file.profiles);
+        Ok(config)
+    }
+
+    /// Profiles for the public clusters and a local test validator.
+    pub fn builtin() -> Self {
+        let profile = |rpc_url: &str| ProfileConfig {
+            rpc_url: rpc_url.to_string(),
+            ws_url: None,
+            commitment: None,
+            keypair: None,
+            cluster: None,
+            program_ids: BTreeMap::new(),
+        };
+
+        Config {
+            active: Some(DEFAULT_PROFILE.to_string()),
+            profiles: BTreeMap::from([
+                ("localnet".to_string(), profile("http://127.0.0.1:8899")),
+                (
+                    "devnet".to_string(),
+                    profile("https://api.devnet.solana.com"),
+                ),
+                (
+                    "testnet".to_string(),
+                    profile("https://api.testnet.solana.com"),
+                ),
+                (
+                    "mainnet".to_string(),
+                    profile("https://api.mainnet-beta.solana.com"),
+                ),
+            ]),
+        }
+    }
+
+    /// The profile called `name`, or the active one.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, ConfigError> {
+        let name = name
+            .or(self.active.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+            .to_string();
+        let profile = self
+            .profiles
+            .get(&name)
+            .ok_or_else(|| ConfigError::UnknownProfile(name.clone()))?;
+
+        let commitment = match &profile.commitment {
+            Some(value) => CommitmentConfig::from_str(value).map_err(|_| {
+                ConfigError::InvalidCommitment {
+                    profile: name.clone(),
+                    value: value.clone(),
+                }
+            })?,
+            None => CommitmentConfig::confirmed(),
+        };
+
+        let cluster = match profile.cluster.as_deref() {
+            None => Cluster::from_rpc_url(&profile.rpc_url),
+            Some("mainnet-beta" | "mainnet") => Cluster::MainnetBeta,
+            Some("testnet") => Cluster::Testnet,
+            Some("devnet") => Cluster::Devnet,
+            Some("custom" | "localnet") => {
+                Cluster::Custom(profile.rpc_url.clone())
+            }
+            Some(value) => {
+                return Err(ConfigError::InvalidCluster {
+                    profile: name,
+                    value: value.to_string(),
+                })
+            }
+        };
+
+        let mut program_ids = BTreeMap::new();
+        if cluster == Cluster::Devnet {
+            program_ids.extend(default_program_ids());
+        }
+        program_ids.extend(profile.program_ids.clone());
+        let program_ids = program_ids
+            .iter()
+            .map(|(program, value)| {
+                let program_id = value.parse().map_err(|_| {
+                    ConfigError::InvalidProgramId {
+                        profile: name.clone(),
+                        name: program.clone(),
+                        value: value.clone(),
+                    }
+                })?;
+                Ok((program.clone(), program_id))
+            })
+            .collect::<Result<_, ConfigError>>()?;
+
+        Ok(Profile {
+            rpc_url: profile.rpc_url.clone(),
+            ws_url: profile
+                .ws_url
+                .clone()
+                .unwrap_or_else(|| websocket_url(&profile.rpc_url)),
+            commitment,
+            keypair: profile.keypair.clone(),
+            cluster,
+            program_ids,
+            name,
+        })
+    }
+}
+
+impl Profile {
+    /// Points the profile at another RPC endpoint, deriving the websocket
+    /// endpoint and cluster from it.
+    pub fn set_rpc_url(&mut self, rpc_url: &str) {
+        self.rpc_url = rpc_url.to_string();
+        self.ws_url = websocket_url(rpc_url);
+        self.cluster = Cluster::from_rpc_url(rpc_url);
+    }
+
+    pub fn program_id(&self, name: &str) -> Option<Pubkey> {
+        self.program_ids.get(name).copied()
+    }
+
+    /// Loads the profile's keypair, falling back to `PRIVATE_KEY` or the
+    /// Solana CLI keypair.
+    pub fn load_keypair(&self) -> Result<Keypair, KeypairError> {
+        match &self.keypair {
+            Some(source) => keypair::load_keypair(source, None),
+            None => keypair::load_default_keypair(),
+        }
+    }
+}
+
+/// `~/.config/solana-course/config.toml`.
+pub fn default_config_path() -> PathBuf {
+    keypair::expand_tilde("~/.config/solana-course/config.toml")
+}
+
+/// Loads the profile called `name`, or the active one, from the default
+/// config file.
+pub fn load_profile(name: Option<&str>) -> Result<Profile, ConfigError> {
+    Config::load(&default_config_path())?.profile(name)
+}
+
+/// Program ids of the course programs as deployed on devnet, used by devnet
+/// profiles that do not set their own. Other clusters have no shared
+/// deployment, so their profiles list every program they use.
+fn default_program_ids() -> BTreeMap<String, String> {
+    BTreeMap::from([
+        (
+            STUDENT_INTRO_PROGRAM.to_string(),
+            STUDENT_INTRO_PROGRAM_ID.to_string(),
+        ),
+        (HELLO_WORLD_PROGRAM.to_string(), HELLO_WORLD_PROGRAM_ID.to_string()),
+    ])
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~' => char::from(byte).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_public_clusters() {
+        assert_eq!(
+            Cluster::from_rpc_url("https://api.mainnet-beta.solana.com"),
+            Cluster::MainnetBeta
+        );
+        assert_eq!(
+            Cluster::from_rpc_url("https://api.testnet.solana.com/"),
+            Cluster::Testnet
+        );
+        assert_eq!(
+            Cluster::from_rpc_url("http://api.devnet.solana.com:80"),
+            Cluster::Devnet
+        );
+        assert_eq!(
+            Cluster::from_rpc_url("api.devnet.solana.com"),
+            Cluster::Devnet
+        );
+        assert_eq!(
+            Cluster::from_rpc_url("http://127.0.0.1:8899"),
+            Cluster::Custom("http://127.0.0.1:8899".to_string())
+        );
+        assert_eq!(
+            Cluster::from_rpc_url("https://api.devnet.solana.com.example"),
+            Cluster::Custom(
+                "https://api.devnet.solana.com.example".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn only_mainnet_has_no_faucet() {
+        assert!(!Cluster::MainnetBeta.supports_airdrops());
+        assert!(Cluster::Devnet.supports_airdrops());
+        assert!(Cluster::Custom("http://127.0.0.1:8899".to_string())
+            .supports_airdrops());
+    }
+
+    #[test]
+    fn explorer_urls_name_the_cluster() {
+        let address = Pubkey::new_unique();
+        assert_eq!(
+            Cluster::MainnetBeta.explorer_address_url(&address),
+            format!("{EXPLORER_URL}/address/{address}")
+        );
+        assert_eq!(
+            Cluster::Devnet.explorer_transaction_url(&Signature::default()),
+            format!(
+                "{EXPLORER_URL}/tx/{}?cluster=devnet",
+                Signature::default()
+            )
+        );
+    }
+
+    #[test]
+    fn explorer_urls_percent_encode_custom_clusters() {
+        let cluster =
+            Cluster::Custom("http://127.0.0.1:8899/?a=b c".to_string());
+        assert_eq!(
+            cluster.explorer_address_url(&Pubkey::default()),
+            format!(
+                "{EXPLORER_URL}/address/{}?cluster=custom&customUrl=\
+                 http%3A%2F%2F127.0.0.1%3A8899%2F%3Fa%3Db%20c",
+                Pubkey::default()
+            )
+        );
+        assert_eq!(percent_encode("a-z_0.9~"), "a-z_0.9~");
+    }
+
+    #[test]
+    fn builtin_profiles() {
+        let profile = Config::builtin().profile(Some("devnet")).unwrap();
+        assert_eq!(profile.cluster, Cluster::Devnet);
+        assert_eq!(profile.ws_url, "wss://api.devnet.solana.com");
+        assert_eq!(
+            profile.program_id(STUDENT_INTRO_PROGRAM),
+            Some(STUDENT_INTRO_PROGRAM_ID.parse().unwrap())
+        );
+        // The mint-enabled program has no shared deployment.
+        assert_eq!(profile.program_id(STUDENT_INTRO_MINT_PROGRAM), None);
+        assert!(matches!(
+            Config::builtin().profile(Some("nope")),
+            Err(ConfigError::UnknownProfile(name)) if name == "nope"
+        ));
+    }
+
+    #[test]
+    fn only_devnet_profiles_get_default_program_ids() {
+        for name in ["mainnet", "testnet", "localnet"] {
+            let profile = Config::builtin().profile(Some(name)).unwrap();
+            assert!(profile.program_ids.is_empty(), "{name}");
+        }
+
+        let file: Config = toml::from_str(
+            r#"
+            [profiles.dev]
+            rpc_url = "http://127.0.0.1:8899"
+            cluster = "devnet"
+
+            [profiles.dev.program_ids]
+            hello_world = "11111111111111111111111111111111"
+
+            [profiles.local]
+            rpc_url = "http://127.0.0.1:8899"
+            "#,
+        )
+        .unwrap();
+
+        let dev = file.profile(Some("dev")).unwrap();
+        assert_eq!(
+            dev.program_id(STUDENT_INTRO_PROGRAM),
+            Some(STUDENT_INTRO_PROGRAM_ID.parse().unwrap())
+        );
+        // Configured ids win over the devnet defaults.
+        assert_eq!(
+            dev.program_id(HELLO_WORLD_PROGRAM),
+            Some(Pubkey::default())
+        );
+        let local = file.profile(Some("local")).unwrap();
+        assert_eq!(local.program_id(STUDENT_INTRO_PROGRAM), None);
+    }
+}
@@ -0,0 +1,230 @@
+use borsh::BorshDeserialize;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use student_intro_program_mint::state::{
+    Reply, ReplyCounter, StudentInfo, COUNTER_DISCRIMINATOR,
+    REPLY_DISCRIMINATOR,
+};
+
+/// An account owned by one of the module_4 student intro programs.
+#[derive(Debug)]
+pub enum IntroAccount {
+    StudentInfo(StudentInfo),
+    ReplyCounter(ReplyCounter),
+    Reply(Reply),
+    /// Data that matches none of the known layouts.
+    Unknown,
+}
+
+impl IntroAccount {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IntroAccount::StudentInfo(_) => "StudentInfo",
+            IntroAccount::ReplyCounter(_) => "ReplyCounter",
+            IntroAccount::Reply(_) => "Reply",
+            IntroAccount::Unknown => "Unknown",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DecodeFailure {
+    pub pubkey: Pubkey,
+    pub reason: String,
+}
+
+/// Sorts account data into one of the known account types.
+///
+/// `ReplyCounter` and `Reply` start with their Borsh string discriminator.
+/// `StudentInfo` is written without one, so data is only taken for an intro
+/// when it is initialized, decodes in full and is followed by nothing but
+/// the zeroed rest of the account. Anything else, such as a zeroed or
+/// foreign account, is `Unknown`.
+pub fn decode_account(data: &[u8]) -> Result<IntroAccount, String> {
+    match discriminator(data) {
+        Some(COUNTER_DISCRIMINATOR) => {
+            decode(data).map(IntroAccount::ReplyCounter)
+        }
+        Some(REPLY_DISCRIMINATOR) => decode(data).map(IntroAccount::Reply),
+        _ => Ok(decode_student_info(data)
+            .map_or(IntroAccount::Unknown, IntroAccount::StudentInfo)),
+    }
+}
+
+/// Decodes every account, collecting failures instead of stopping at the
+/// first one.
+pub fn decode_accounts<I>(
+    accounts: I,
+) -> (Vec<(Pubkey, IntroAccount)>, Vec<DecodeFailure>)
+where
+    I: IntoIterator<Item = (Pubkey, Account)>,
+{
+    let mut decoded = Vec::new();
+    let mut failures = Vec::new();
+
+    for (pubkey, account) in accounts {
+        match decode_account(&account.data) {
+            Ok(data) => decoded.push((pubkey, data)),
+            Err(reason) => failures.push(DecodeFailure {
+                pubkey,
+                reason,
+            }),
+        }
+    }
+
+    (decoded, failures)
+}
+
+fn discriminator(data: &[u8]) -> Option<&str> {
+    let (len, rest) = data.split_first_chunk::<4>()?;
+    let len = u32::from_le_bytes(*len) as usize;
+    std::str::from_utf8(rest.get(..len)?).ok()
+}
+
+fn decode_student_info(mut data: &[u8]) -> Option<StudentInfo> {
+    let info = StudentInfo::deserialize(&mut data).ok()?;
+    let padded = data.iter().all(|byte| *byte == 0);
+    (info.is_initialized && padded).then_some(info)
+}
+
+fn decode<T: BorshDeserialize>(mut data: &[u8]) -> Result<T, String> {
+    T::deserialize(&mut data).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    /// Serialized `value` at the start of a zeroed 1000 byte account, as the
+    /// program allocates them.
+    fn account_data<T: BorshSerialize>(value: &T) -> Vec<u8> {
+        let mut data = value.try_to_vec().unwrap();
+        data.resize(1000, 0);
+        data
+    }
+
+    #[test]
+    fn detects_student_info() {
+        let data = account_data(&StudentInfo {
+            is_initialized: true,
+            name: "alice".to_string(),
+            msg: "hello".to_string(),
+        });
+
+        match decode_account(&data).unwrap() {
+            IntroAccount::StudentInfo(info) => {
+                assert_eq!(info.name, "alice");
+                assert_eq!(info.msg, "hello");
+            }
+            other => panic!("decoded as {}", other.kind()),
+        }
+    }
+
+    #[test]
+    fn detects_reply_counter() {
+        let data = account_data(&ReplyCounter {
+            discriminator: COUNTER_DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            counter: 3,
+        });
+
+        match decode_account(&data).unwrap() {
+            IntroAccount::ReplyCounter(counter) => {
+                assert_eq!(counter.counter, 3)
+            }
+            other => panic!("decoded as {}", other.kind()),
+        }
+    }
+
+    #[test]
+    fn detects_reply() {
+        let studentinfo = Pubkey::new_unique();
+        let data = account_data(&Reply {
+            discriminator: REPLY_DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            studentinfo,
+            reply: "welcome".to_string(),
+        });
+
+        match decode_account(&data).unwrap() {
+            IntroAccount::Reply(reply) => {
+                assert_eq!(reply.studentinfo, studentinfo);
+                assert_eq!(reply.reply, "welcome");
+            }
+            other => panic!("decoded as {}", other.kind()),
+        }
+    }
+
+    #[test]
+    fn unknown_layouts_are_not_errors() {
+        assert!(matches!(decode_account(&[]), Ok(IntroAccount::Unknown)));
+        assert!(matches!(decode_account(&[7; 32]), Ok(IntroAccount::Unknown)));
+        assert!(matches!(
+            decode_account(&[0; 1000]),
+            Ok(IntroAccount::Unknown)
+        ));
+    }
+
+    #[test]
+    fn student_info_needs_a_full_initialized_decode() {
+        let uninitialized = account_data(&StudentInfo {
+            is_initialized: false,
+            name: "alice".to_string(),
+            msg: "hello".to_string(),
+        });
+        assert!(matches!(
+            decode_account(&uninitialized),
+            Ok(IntroAccount::Unknown)
+        ));
+
+        // A name length running past the end of the data.
+        assert!(matches!(
+            decode_account(&[1, 255, 0, 0, 0, b'a']),
+            Ok(IntroAccount::Unknown)
+        ));
+
+        // Foreign data that happens to start with a decodable intro.
+        let mut trailing = account_data(&StudentInfo {
+            is_initialized: true,
+            name: String::new(),
+            msg: String::new(),
+        });
+        trailing[100] = 9;
+        assert!(matches!(decode_account(&trailing), Ok(IntroAccount::Unknown)));
+    }
+
+    #[test]
+    fn collects_failures() {
+        let valid = Account {
+            data: account_data(&StudentInfo {
+                is_initialized: true,
+                name: String::new(),
+                msg: String::new(),
+            }),
+            ..Account::default()
+        };
+        // A reply counter cut off before its count.
+        let mut truncated = ReplyCounter {
+            discriminator: COUNTER_DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            counter: 0,
+        }
+        .try_to_vec()
+        .unwrap();
+        truncated.pop();
+        let truncated = Account {
+            data: truncated,
+            ..Account::default()
+        };
+        let truncated_key = Pubkey::new_unique();
+
+        let (decoded, failures) = decode_accounts([
+            (Pubkey::new_unique(), valid),
+            (truncated_key, truncated),
+        ]);
+
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].pubkey, truncated_key);
+    }
+}
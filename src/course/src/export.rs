@@ -0,0 +1,346 @@
+use crate::{
+    decode::DecodeFailure,
+    fetch::{fetch_account_chunks, FetchConfig},
+    intro::StudentIntro,
+    query::IntroQuery,
+};
+use borsh::BorshDeserialize;
+use futures::TryStreamExt;
+use serde::Serialize;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    client_error::ClientError,
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::{io::Write, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    #[error("failed to decode {pubkey}: {source}")]
+    Decode {
+        pubkey: Pubkey,
+        source: std::io::Error,
+    },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// A single pretty-printed JSON array.
+    Json,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            _ => Err(format!("unknown export format `{s}`")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntroRecord {
+    pub pubkey: String,
+    pub lamports: u64,
+    pub data_len: usize,
+    pub variant: u8,
+    pub name: String,
+    pub message: String,
+}
+
+impl IntroRecord {
+    pub fn decode(
+        pubkey: &Pubkey,
+        account: &Account,
+    ) -> Result<Self, ExportError> {
+        let intro = StudentIntro::deserialize(&mut account.data.as_slice())
+            .map_err(|source| ExportError::Decode {
+                pubkey: *pubkey,
+                source,
+            })?;
+
+        Ok(Self {
+            pubkey: pubkey.to_string(),
+            lamports: account.lamports,
+            data_len: account.data.len(),
+            variant: intro.variant,
+            name: intro.name,
+            message: intro.message,
+        })
+    }
+}
+
+/// Writes records one at a time in the chosen format.
+pub struct RecordWriter<W: Write> {
+    sink: Sink<W>,
+    count: usize,
+}
+
+enum Sink<W: Write> {
+    Csv(Box<csv::Writer<W>>),
+    Json(W),
+    Ndjson(W),
+}
+
+impl<W: Write> RecordWriter<W> {
+    pub fn new(format: ExportFormat, out: W) -> Self {
+        let sink = match format {
+            ExportFormat::Csv => {
+                Sink::Csv(Box::new(csv::Writer::from_writer(out)))
+            }
+            ExportFormat::Json => Sink::Json(out),
+            ExportFormat::Ndjson => Sink::Ndjson(out),
+        };
+
+        Self {
+            sink,
+            count: 0,
+        }
+    }
+
+    pub fn write<T: Serialize>(
+        &mut self,
+        record: &T,
+    ) -> Result<(), ExportError> {
+        match &mut self.sink {
+            Sink::Csv(writer) => writer.serialize(record)?,
+            Sink::Json(out) => {
+                let separator = if self.count == 0 {
+                    "["
+                } else {
+                    ","
+                };
+                let json = serde_json::to_string_pretty(record)?;
+                writeln!(out, "{separator}")?;
+                write!(out, "  {}", json.replace('\n', "\n  "))?;
+            }
+            Sink::Ndjson(out) => {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+        }
+
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Closes the output and returns the number of records written.
+    pub fn finish(self) -> Result<usize, ExportError> {
+        match self.sink {
+            Sink::Csv(mut writer) => writer.flush()?,
+            Sink::Json(mut out) => {
+                if self.count == 0 {
+                    writeln!(out, "[]")?;
+                } else {
+                    writeln!(out, "\n]")?;
+                }
+                out.flush()?;
+            }
+            Sink::Ndjson(mut out) => out.flush()?,
+        }
+
+        Ok(self.count)
+    }
+}
+
+/// Outcome of an export.
+#[derive(Debug)]
+pub struct ExportSummary {
+    /// Number of records written.
+    pub exported: usize,
+    /// Accounts left out because their data is not a student intro.
+    pub failures: Vec<DecodeFailure>,
+}
+
+/// Streams every student intro matching `query` to `out`, fetching accounts
+/// in chunks so only one chunk of decoded data is held at a time. Accounts
+/// that fail to decode are skipped and listed in the summary.
+pub async fn export_intros<W: Write>(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    query: &IntroQuery,
+    format: ExportFormat,
+    out: W,
+    fetch_config: FetchConfig,
+) -> Result<ExportSummary, ExportError> {
+    let keys = get_program_account_keys(client, program_id, query).await?;
+    let mut writer = RecordWriter::new(format, out);
+    let mut failures = Vec::new();
+
+    let mut chunks = fetch_account_chunks(client, &keys, fetch_config);
+    while let Some(chunk) = chunks.try_next().await? {
+        for (pubkey, account) in chunk {
+            let Some(account) = account else {
+                continue;
+            };
+            match IntroRecord::decode(&pubkey, &account) {
+                Ok(record) => writer.write(&record)?,
+                Err(ExportError::Decode {
+                    pubkey,
+                    source,
+                }) => failures.push(DecodeFailure {
+                    pubkey,
+                    reason: source.to_string(),
+                }),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    Ok(ExportSummary {
+        exported: writer.finish()?,
+        failures,
+    })
+}
+
+async fn get_program_account_keys(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    query: &IntroQuery,
+) -> Result<Vec<Pubkey>, ClientError> {
+    let config = RpcProgramAccountsConfig {
+        filters: query.rpc_filters(),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: 0,
+            }),
+            ..RpcAccountInfoConfig::default()
+        },
+        with_context: None,
+    };
+
+    let accounts =
+        client.get_program_accounts_with_config(program_id, config).await?;
+
+    Ok(accounts.into_iter().map(|(pubkey, _)| pubkey).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use serde_json::{json, Value};
+
+    #[derive(Serialize)]
+    struct Row {
+        name: &'static str,
+        count: u8,
+    }
+
+    const ROWS: [Row; 2] = [
+        Row {
+            name: "alice",
+            count: 1,
+        },
+        Row {
+            name: "bob, jr",
+            count: 2,
+        },
+    ];
+
+    fn export(format: ExportFormat, rows: &[Row]) -> String {
+        let mut out = Vec::new();
+        let mut writer = RecordWriter::new(format, &mut out);
+        for row in rows {
+            writer.write(row).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), rows.len());
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn json_is_one_array() {
+        assert_eq!(export(ExportFormat::Json, &[]), "[]\n");
+
+        let out = export(ExportFormat::Json, &ROWS);
+        let value: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(
+            value,
+            json!([
+                { "name": "alice", "count": 1 },
+                { "name": "bob, jr", "count": 2 },
+            ])
+        );
+        assert!(out.starts_with("[\n  {"));
+        assert!(out.ends_with("}\n]\n"));
+    }
+
+    #[test]
+    fn ndjson_writes_a_line_per_record() {
+        assert_eq!(export(ExportFormat::Ndjson, &[]), "");
+        assert_eq!(
+            export(ExportFormat::Ndjson, &ROWS),
+            "{\"name\":\"alice\",\"count\":1}\n\
+             {\"name\":\"bob, jr\",\"count\":2}\n"
+        );
+    }
+
+    #[test]
+    fn csv_has_a_header_and_quotes_fields() {
+        assert_eq!(
+            export(ExportFormat::Csv, &ROWS),
+            "name,count\nalice,1\n\"bob, jr\",2\n"
+        );
+    }
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!("CSV".parse(), Ok(ExportFormat::Csv));
+        assert_eq!("jsonl".parse(), Ok(ExportFormat::Ndjson));
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+
+    #[test]
+    fn decodes_intro_records() {
+        let pubkey = Pubkey::new_unique();
+        let intro = StudentIntro {
+            variant: 0,
+            name: "alice".to_string(),
+            message: "hello".to_string(),
+        };
+        let account = Account {
+            lamports: 7,
+            data: intro.try_to_vec().unwrap(),
+            ..Account::default()
+        };
+
+        let record = IntroRecord::decode(&pubkey, &account).unwrap();
+        assert_eq!(record.pubkey, pubkey.to_string());
+        assert_eq!(record.lamports, 7);
+        assert_eq!(record.name, "alice");
+        assert_eq!(record.message, "hello");
+
+        let truncated = Account {
+            data: account.data[..3].to_vec(),
+            ..account
+        };
+        assert!(matches!(
+            IntroRecord::decode(&pubkey, &truncated),
+            Err(ExportError::Decode { pubkey: key, .. }) if key == pubkey
+        ));
+    }
+}
@@ -0,0 +1,121 @@
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use solana_client::{
+    client_error::Result as ClientResult, nonblocking::rpc_client::RpcClient,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+/// Largest number of keys a single `getMultipleAccounts` request accepts.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FetchConfig {
+    /// Keys per `getMultipleAccounts` request, capped at
+    /// [`MAX_MULTIPLE_ACCOUNTS`].
+    pub chunk_size: usize,
+    /// Number of requests in flight at once.
+    pub concurrency: usize,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: MAX_MULTIPLE_ACCOUNTS,
+            concurrency: 4,
+        }
+    }
+}
+
+/// Fetches `keys` in RPC-sized chunks, running up to `config.concurrency`
+/// requests at a time.
+///
+/// The result is in the same order as `keys`, with `None` for accounts that
+/// do not exist.
+pub async fn fetch_accounts(
+    client: &RpcClient,
+    keys: &[Pubkey],
+    config: FetchConfig,
+) -> ClientResult<Vec<Option<Account>>> {
+    let chunks = fetch_account_chunks(client, keys, config)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(chunks.into_iter().flatten().map(|(_, account)| account).collect())
+}
+
+/// Streaming version of [`fetch_accounts`] that yields each chunk as soon as
+/// it and every chunk before it have arrived, so callers can process large
+/// key lists without holding every account in memory.
+pub fn fetch_account_chunks<'a>(
+    client: &'a RpcClient,
+    keys: &'a [Pubkey],
+    config: FetchConfig,
+) -> impl Stream<Item = ClientResult<Vec<(Pubkey, Option<Account>)>>> + 'a {
+    let chunk_size = config.chunk_size.clamp(1, MAX_MULTIPLE_ACCOUNTS);
+
+    stream::iter(keys.chunks(chunk_size))
+        .map(move |chunk| async move {
+            let accounts = client.get_multiple_accounts(chunk).await?;
+            Ok(chunk.iter().copied().zip(accounts).collect())
+        })
+        .buffered(config.concurrency.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn keys(n: usize) -> Vec<Pubkey> {
+        (0..n).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn chunks_keep_the_key_order() {
+        // The mock answers every request with two missing accounts.
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let keys = keys(5);
+        let config = FetchConfig {
+            chunk_size: 2,
+            concurrency: 2,
+        };
+
+        let chunks = block_on(
+            fetch_account_chunks(&client, &keys, config)
+                .try_collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        let sizes: Vec<_> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [2, 2, 1]);
+        let fetched: Vec<_> =
+            chunks.into_iter().flatten().map(|(key, _)| key).collect();
+        assert_eq!(fetched, keys);
+    }
+
+    #[test]
+    fn clamps_chunk_size_and_concurrency() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let keys = keys(3);
+        let config = FetchConfig {
+            chunk_size: 0,
+            concurrency: 0,
+        };
+
+        let chunks = block_on(
+            fetch_account_chunks(&client, &keys, config)
+                .try_collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(chunks.len(), 3);
+    }
+
+    #[test]
+    fn request_errors_are_returned() {
+        let client = RpcClient::new_mock("fails".to_string());
+
+        let result =
+            block_on(fetch_accounts(&client, &keys(3), FetchConfig::default()));
+
+        assert!(result.is_err());
+    }
+}
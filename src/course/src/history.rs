@@ -0,0 +1,401 @@
+use crate::intro_error::describe_transaction_error;
+use solana_client::{
+    client_error::{ClientErrorKind, Result as ClientResult},
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+    rpc_request::MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
+};
+use solana_sdk::{
+    instruction::CompiledInstruction, pubkey::Pubkey, signature::Signature,
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, UiInstruction,
+    UiLoadedAddresses, UiTransactionEncoding,
+};
+use student_intro_program_mint::instruction::IntroInstruction;
+
+/// One instruction addressed to the student intro program.
+#[derive(Clone, Debug)]
+pub struct IntroCall {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// The instruction's first account, the signer that initialized it.
+    pub caller: Option<Pubkey>,
+    /// Program of the top level instruction this one was invoked from
+    /// through a CPI, `None` for top level instructions.
+    pub invoked_by: Option<Pubkey>,
+    /// The decoded instruction, or why its data could not be decoded.
+    pub instruction: Result<IntroInstruction, String>,
+    /// Why the transaction failed, `None` if it succeeded.
+    pub error: Option<String>,
+}
+
+impl IntroCall {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HistoryPage {
+    /// Calls of the page's transactions, newest transaction first.
+    pub calls: Vec<IntroCall>,
+    /// Pass as `before` to fetch the next page, `None` on the last page.
+    pub next: Option<Signature>,
+}
+
+/// Fetches up to `limit` of the program's transactions older than `before`
+/// and decodes the intro instructions in them, including inner ones. The
+/// limit is clamped to the 1000 signatures a node returns at most.
+pub fn get_intro_history(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    before: Option<Signature>,
+    limit: usize,
+) -> ClientResult<HistoryPage> {
+    let limit = limit.clamp(1, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT);
+    let signatures = client.get_signatures_for_address_with_config(
+        program_id,
+        GetConfirmedSignaturesForAddress2Config {
+            before,
+            until: None,
+            limit: Some(limit),
+            commitment: Some(client.commitment()),
+        },
+    )?;
+
+    let mut calls = Vec::new();
+    for status in &signatures {
+        let signature = status.signature.parse().map_err(|_| {
+            ClientErrorKind::Custom(format!(
+                "invalid signature {}",
+                status.signature
+            ))
+        })?;
+        let transaction = client.get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(client.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        calls.extend(intro_calls(&signature, &transaction, program_id));
+    }
+
+    let next = match signatures.last() {
+        Some(status) if signatures.len() == limit => {
+            status.signature.parse().ok()
+        }
+        _ => None,
+    };
+
+    Ok(HistoryPage {
+        calls,
+        next,
+    })
+}
+
+/// Intro instructions of a fetched transaction in execution order.
+pub fn intro_calls(
+    signature: &Signature,
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    program_id: &Pubkey,
+) -> Vec<IntroCall> {
+    let Some(decoded) = transaction.transaction.transaction.decode() else {
+        return Vec::new();
+    };
+    let meta = transaction.transaction.meta.as_ref();
+
+    let mut account_keys = decoded.message.static_account_keys().to_vec();
+    let loaded: Option<UiLoadedAddresses> =
+        meta.and_then(|meta| meta.loaded_addresses.clone().into());
+    if let Some(loaded) = loaded {
+        account_keys.extend(
+            loaded
+                .writable
+                .iter()
+                .chain(&loaded.readonly)
+                .filter_map(|key| key.parse::<Pubkey>().ok()),
+        );
+    }
+
+    let programs = decoded
+        .message
+        .instructions()
+        .iter()
+        .filter_map(|instruction| {
+            account_keys.get(usize::from(instruction.program_id_index))
+        })
+        .copied()
+        .collect::<Vec<_>>();
+    let error = meta
+        .and_then(|meta| meta.err.as_ref())
+        .map(|err| describe_transaction_error(err, &programs, program_id));
+    let inner: Vec<_> = meta
+        .and_then(|meta| meta.inner_instructions.clone().into())
+        .unwrap_or_default();
+
+    let call = |instruction: &CompiledInstruction, invoked_by| {
+        let program =
+            account_keys.get(usize::from(instruction.program_id_index));
+        if program != Some(program_id) {
+            return None;
+        }
+        Some(IntroCall {
+            signature: *signature,
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+            caller: instruction
+                .accounts
+                .first()
+                .and_then(|i| account_keys.get(usize::from(*i)))
+                .copied(),
+            invoked_by,
+            instruction: IntroInstruction::unpack(&instruction.data)
+                .map_err(|e| e.to_string()),
+            error: error.clone(),
+        })
+    };
+
+    let mut calls = Vec::new();
+    for (i, instruction) in decoded.message.instructions().iter().enumerate() {
+        calls.extend(call(instruction, None));
+
+        let outer = account_keys
+            .get(usize::from(instruction.program_id_index))
+            .copied();
+        let inner = inner
+            .iter()
+            .filter(|inner| usize::from(inner.index) == i)
+            .flat_map(|inner| &inner.instructions);
+        for instruction in inner {
+            let UiInstruction::Compiled(instruction) = instruction else {
+                continue;
+            };
+            let Ok(data) = bs58::decode(&instruction.data).into_vec() else {
+                continue;
+            };
+            let instruction = CompiledInstruction {
+                program_id_index: instruction.program_id_index,
+                accounts: instruction.accounts.clone(),
+                data,
+            };
+            calls.extend(call(&instruction, outer));
+        }
+    }
+
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{prelude::BASE64_STANDARD, Engine};
+    use borsh::BorshSerialize;
+    use serde_json::json;
+    use solana_client::rpc_request::RpcRequest;
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction, InstructionError},
+        message::Message,
+        transaction::{Transaction, TransactionError},
+    };
+    use solana_transaction_status::{
+        option_serializer::OptionSerializer, EncodedTransaction,
+        EncodedTransactionWithStatusMeta, TransactionBinaryEncoding,
+        UiCompiledInstruction, UiInnerInstructions, UiTransactionStatusMeta,
+    };
+    use std::collections::HashMap;
+
+    fn intro_data(variant: u8, fields: &[&str]) -> Vec<u8> {
+        let mut data = vec![variant];
+        for field in fields {
+            field.serialize(&mut data).unwrap();
+        }
+        data
+    }
+
+    /// A transaction whose first instruction calls `router`, which invokes
+    /// the intro program, and whose second calls the intro program itself.
+    fn transaction(
+        payer: &Pubkey,
+        router: &Pubkey,
+        program_id: &Pubkey,
+        err: Option<TransactionError>,
+    ) -> EncodedConfirmedTransactionWithStatusMeta {
+        let message = Message::new(
+            &[
+                Instruction::new_with_bytes(
+                    *router,
+                    &[],
+                    vec![AccountMeta::new_readonly(*program_id, false)],
+                ),
+                Instruction::new_with_bytes(
+                    *program_id,
+                    &intro_data(0, &["Ada", "hello"]),
+                    vec![AccountMeta::new(*payer, true)],
+                ),
+            ],
+            Some(payer),
+        );
+        let program_index = message
+            .account_keys
+            .iter()
+            .position(|key| key == program_id)
+            .unwrap();
+        let transaction = Transaction::new_unsigned(message);
+        let inner = UiInnerInstructions {
+            index: 0,
+            instructions: vec![UiInstruction::Compiled(
+                UiCompiledInstruction {
+                    program_id_index: program_index as u8,
+                    accounts: vec![0],
+                    data: bs58::encode(intro_data(2, &["hi"])).into_string(),
+                    stack_height: None,
+                },
+            )],
+        };
+
+        EncodedConfirmedTransactionWithStatusMeta {
+            slot: 7,
+            block_time: Some(1_700_000_000),
+            transaction: EncodedTransactionWithStatusMeta {
+                version: None,
+                transaction: EncodedTransaction::Binary(
+                    BASE64_STANDARD
+                        .encode(bincode::serialize(&transaction).unwrap()),
+                    TransactionBinaryEncoding::Base64,
+                ),
+                meta: Some(UiTransactionStatusMeta {
+                    status: err.clone().map_or(Ok(()), Err),
+                    err,
+                    fee: 5000,
+                    pre_balances: Vec::new(),
+                    post_balances: Vec::new(),
+                    inner_instructions: OptionSerializer::Some(vec![inner]),
+                    log_messages: OptionSerializer::None,
+                    pre_token_balances: OptionSerializer::None,
+                    post_token_balances: OptionSerializer::None,
+                    rewards: OptionSerializer::None,
+                    loaded_addresses: OptionSerializer::Skip,
+                    return_data: OptionSerializer::Skip,
+                    compute_units_consumed: OptionSerializer::Skip,
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn decodes_top_level_and_inner_calls_in_order() {
+        let payer = Pubkey::new_unique();
+        let router = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let signature = Signature::new_unique();
+        let transaction = transaction(&payer, &router, &program_id, None);
+
+        let calls = intro_calls(&signature, &transaction, &program_id);
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].invoked_by, Some(router));
+        assert_eq!(calls[0].caller, Some(payer));
+        assert_eq!(
+            calls[0].instruction,
+            Ok(IntroInstruction::Reply {
+                reply: "hi".to_string()
+            })
+        );
+        assert_eq!(calls[1].invoked_by, None);
+        assert_eq!(
+            calls[1].instruction,
+            Ok(IntroInstruction::CreateAccount {
+                name: "Ada".to_string(),
+                message: "hello".to_string(),
+            })
+        );
+        assert!(calls.iter().all(|call| call.signature == signature
+            && call.slot == 7
+            && call.block_time == Some(1_700_000_000)
+            && call.succeeded()));
+    }
+
+    #[test]
+    fn failed_transactions_describe_the_failing_program() {
+        let program_id = Pubkey::new_unique();
+        // Code 0 of the router must not be read as an intro program error.
+        let err =
+            TransactionError::InstructionError(0, InstructionError::Custom(0));
+        let transaction = transaction(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &program_id,
+            Some(err),
+        );
+
+        let calls =
+            intro_calls(&Signature::new_unique(), &transaction, &program_id);
+
+        assert_eq!(calls.len(), 2);
+        for call in calls {
+            assert_eq!(
+                call.error.as_deref(),
+                Some("Instruction 0 failed: custom error 0")
+            );
+        }
+    }
+
+    #[test]
+    fn other_programs_have_no_calls() {
+        let transaction = transaction(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            None,
+        );
+
+        assert!(intro_calls(
+            &Signature::new_unique(),
+            &transaction,
+            &Pubkey::new_unique()
+        )
+        .is_empty());
+    }
+
+    fn signatures(count: usize) -> serde_json::Value {
+        let statuses = (0..count)
+            .map(|_| {
+                json!({
+                    "signature": Signature::new_unique().to_string(),
+                    "slot": 1,
+                    "err": null,
+                    "memo": null,
+                    "blockTime": null,
+                    "confirmationStatus": "finalized",
+                })
+            })
+            .collect::<Vec<_>>();
+        json!(statuses)
+    }
+
+    #[test]
+    fn full_pages_link_to_the_next_one() {
+        let page = |count, limit| {
+            let client = RpcClient::new_mock_with_mocks(
+                "succeeds".to_string(),
+                HashMap::from([(
+                    RpcRequest::GetSignaturesForAddress,
+                    signatures(count),
+                )]),
+            );
+            get_intro_history(&client, &Pubkey::new_unique(), None, limit)
+                .unwrap()
+        };
+
+        assert!(page(2, 2).next.is_some());
+        assert!(page(1, 2).next.is_none());
+        assert!(page(0, 2).next.is_none());
+        // A node never returns more than 1000 signatures, a full page at
+        // that size still has a next one.
+        assert!(page(1000, 5000).next.is_some());
+    }
+}
@@ -0,0 +1,727 @@
+use crate::{
+    decode::{decode_account, IntroAccount},
+    multisig::multisig_signers,
+    token_2022::extensions,
+    token_metadata::{
+        MetadataPointer, TokenMetadata, METADATA_POINTER_EXTENSION,
+        TOKEN_METADATA_EXTENSION,
+    },
+};
+use serde_json::{json, Value};
+use solana_client::{
+    client_error::Result as ClientResult, nonce_utils, rpc_client::RpcClient,
+};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    nonce::state::Data as NonceData,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_token_2022::{
+    extension::{
+        cpi_guard::CpiGuard,
+        default_account_state::DefaultAccountState,
+        interest_bearing_mint::InterestBearingConfig,
+        memo_transfer::MemoTransfer,
+        mint_close_authority::MintCloseAuthority,
+        permanent_delegate::PermanentDelegate,
+        transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+        AccountType, ExtensionType, StateWithExtensions,
+    },
+    pod::pod_from_bytes,
+    state::{Account as TokenAccount, AccountState, Mint, Multisig},
+};
+
+/// An account with its data decoded when the owner is known.
+#[derive(Debug)]
+pub struct Inspection {
+    pub pubkey: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    /// Lamports needed for an account of this data length to be rent
+    /// exempt.
+    pub rent_exempt_minimum: u64,
+    pub data_len: usize,
+    pub executable: bool,
+    pub data: AccountData,
+}
+
+#[derive(Debug)]
+pub enum AccountData {
+    /// A mint of either token program, with its decoded Token-2022
+    /// extensions.
+    Mint {
+        mint: Mint,
+        extensions: Vec<(&'static str, Value)>,
+    },
+    TokenAccount {
+        account: TokenAccount,
+        extensions: Vec<(&'static str, Value)>,
+    },
+    /// An m-of-n authority of either token program.
+    Multisig(Multisig),
+    /// An upgradeable program, its code lives in the program data account.
+    Program {
+        programdata_address: Pubkey,
+    },
+    ProgramData {
+        slot: u64,
+        upgrade_authority_address: Option<Pubkey>,
+    },
+    /// A buffer holding a program being deployed.
+    Buffer {
+        authority_address: Option<Pubkey>,
+    },
+    Nonce(NonceData),
+    Intro(IntroAccount),
+    /// The owner is not one of the known programs.
+    Unknown,
+    /// The owner is known but the data does not match any of its layouts.
+    Invalid(String),
+}
+
+impl AccountData {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AccountData::Mint {
+                ..
+            } => "Mint",
+            AccountData::TokenAccount {
+                ..
+            } => "TokenAccount",
+            AccountData::Multisig(_) => "Multisig",
+            AccountData::Program {
+                ..
+            } => "Program",
+            AccountData::ProgramData {
+                ..
+            } => "ProgramData",
+            AccountData::Buffer {
+                ..
+            } => "Buffer",
+            AccountData::Nonce(_) => "Nonce",
+            AccountData::Intro(intro) => intro.kind(),
+            AccountData::Unknown => "Unknown",
+            AccountData::Invalid(_) => "Invalid",
+        }
+    }
+
+    /// The decoded fields in layout order.
+    pub fn fields(&self) -> Vec<(&'static str, Value)> {
+        match self {
+            AccountData::Mint {
+                mint,
+                extensions,
+            } => [
+                ("mint_authority", optional(mint.mint_authority)),
+                ("supply", json!(mint.supply)),
+                ("decimals", json!(mint.decimals)),
+                ("is_initialized", json!(mint.is_initialized)),
+                ("freeze_authority", optional(mint.freeze_authority)),
+            ]
+            .into_iter()
+            .chain(extensions.iter().cloned())
+            .collect(),
+            AccountData::TokenAccount {
+                account,
+                extensions,
+            } => [
+                ("mint", json!(account.mint.to_string())),
+                ("owner", json!(account.owner.to_string())),
+                ("amount", json!(account.amount)),
+                ("delegate", optional(account.delegate)),
+                ("state", json!(format!("{:?}", account.state))),
+                (
+                    "is_native",
+                    match account.is_native {
+                        COption::Some(rent) => json!(rent),
+                        COption::None => Value::Null,
+                    },
+                ),
+                ("delegated_amount", json!(account.delegated_amount)),
+                ("close_authority", optional(account.close_authority)),
+            ]
+            .into_iter()
+            .chain(extensions.iter().cloned())
+            .collect(),
+            AccountData::Multisig(multisig) => vec![
+                ("m", json!(multisig.m)),
+                ("n", json!(multisig.n)),
+                ("is_initialized", json!(multisig.is_initialized)),
+                (
+                    "signers",
+                    json!(multisig_signers(multisig)
+                        .iter()
+                        .map(Pubkey::to_string)
+                        .collect::<Vec<_>>()),
+                ),
+            ],
+            AccountData::Program {
+                programdata_address,
+            } => vec![(
+                "programdata_address",
+                json!(programdata_address.to_string()),
+            )],
+            AccountData::ProgramData {
+                slot,
+                upgrade_authority_address,
+            } => vec![
+                ("slot", json!(slot)),
+                (
+                    "upgrade_authority_address",
+                    optional(*upgrade_authority_address),
+                ),
+            ],
+            AccountData::Buffer {
+                authority_address,
+            } => vec![("authority_address", optional(*authority_address))],
+            AccountData::Nonce(nonce) => vec![
+                ("authority", json!(nonce.authority.to_string())),
+                ("nonce", json!(nonce.blockhash().to_string())),
+                (
+                    "lamports_per_signature",
+                    json!(nonce.fee_calculator.lamports_per_signature),
+                ),
+            ],
+            AccountData::Intro(IntroAccount::StudentInfo(info)) => vec![
+                ("is_initialized", json!(info.is_initialized)),
+                ("name", json!(info.name)),
+                ("msg", json!(info.msg)),
+            ],
+            AccountData::Intro(IntroAccount::ReplyCounter(counter)) => vec![
+                ("is_initialized", json!(counter.is_initialized)),
+                ("counter", json!(counter.counter)),
+            ],
+            AccountData::Intro(IntroAccount::Reply(reply)) => vec![
+                ("is_initialized", json!(reply.is_initialized)),
+                ("studentinfo", json!(reply.studentinfo.to_string())),
+                ("reply", json!(reply.reply)),
+            ],
+            AccountData::Intro(IntroAccount::Unknown)
+            | AccountData::Unknown => Vec::new(),
+            AccountData::Invalid(reason) => vec![("error", json!(reason))],
+        }
+    }
+}
+
+impl Inspection {
+    pub fn to_json(&self) -> Value {
+        let fields = self
+            .data
+            .fields()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({
+            "pubkey": self.pubkey.to_string(),
+            "owner": self.owner.to_string(),
+            "lamports": self.lamports,
+            "rent_exempt_minimum": self.rent_exempt_minimum,
+            "data_len": self.data_len,
+            "executable": self.executable,
+            "kind": self.data.kind(),
+            "data": fields,
+        })
+    }
+}
+
+/// Fetches an account and decodes its data. Accounts owned by
+/// `intro_program_id` are decoded as student intro program accounts.
+pub fn inspect(
+    client: &RpcClient,
+    pubkey: &Pubkey,
+    intro_program_id: Option<&Pubkey>,
+) -> ClientResult<Inspection> {
+    let account = client.get_account(pubkey)?;
+    let rent_exempt_minimum =
+        client.get_minimum_balance_for_rent_exemption(account.data.len())?;
+
+    Ok(Inspection {
+        pubkey: *pubkey,
+        owner: account.owner,
+        lamports: account.lamports,
+        rent_exempt_minimum,
+        data_len: account.data.len(),
+        executable: account.executable,
+        data: decode_data(&account, intro_program_id),
+    })
+}
+
+/// Decodes the data of an account owned by one of the known programs. Intro
+/// accounts are only recognized when the intro program id is known.
+pub fn decode_data(
+    account: &Account,
+    intro_program_id: Option<&Pubkey>,
+) -> AccountData {
+    let result = if account.owner == spl_token::id()
+        || account.owner == spl_token_2022::id()
+    {
+        decode_token(&account.data)
+    } else if account.owner == bpf_loader_upgradeable::id() {
+        decode_loader(&account.data)
+    } else if account.owner == system_program::id() {
+        if account.data.is_empty() {
+            return AccountData::Unknown;
+        }
+        nonce_utils::data_from_account(account)
+            .map(AccountData::Nonce)
+            .map_err(|e| e.to_string())
+    } else if Some(&account.owner) == intro_program_id {
+        decode_account(&account.data).map(AccountData::Intro)
+    } else {
+        return AccountData::Unknown;
+    };
+
+    result.unwrap_or_else(AccountData::Invalid)
+}
+
+/// Decodes a mint or token account. Token-2022 accounts longer than the
+/// base layout store which of the two they are after the base account.
+fn decode_token(data: &[u8]) -> Result<AccountData, String> {
+    let account_type = match data.len() {
+        Mint::LEN => AccountType::Mint,
+        TokenAccount::LEN => AccountType::Account,
+        // Token-2022 pads accounts with extensions to never be this long.
+        Multisig::LEN => {
+            return Multisig::unpack(data)
+                .map(AccountData::Multisig)
+                .map_err(|e| e.to_string())
+        }
+        len if len > TokenAccount::LEN => {
+            AccountType::try_from(data[TokenAccount::LEN])
+                .map_err(|_| "unknown token account type".to_string())?
+        }
+        len => return Err(format!("unexpected token account size {len}")),
+    };
+
+    match account_type {
+        AccountType::Mint => {
+            let state = StateWithExtensions::<Mint>::unpack(data)
+                .map_err(|e| e.to_string())?;
+            Ok(AccountData::Mint {
+                mint: state.base,
+                extensions: extension_fields(data)?,
+            })
+        }
+        AccountType::Account => {
+            let state = StateWithExtensions::<TokenAccount>::unpack(data)
+                .map_err(|e| e.to_string())?;
+            Ok(AccountData::TokenAccount {
+                account: state.base,
+                extensions: extension_fields(data)?,
+            })
+        }
+        AccountType::Uninitialized => {
+            Err("uninitialized token account".to_string())
+        }
+    }
+}
+
+/// One field per Token-2022 extension, named after it. Extensions this
+/// tool does not decode are listed under `other_extensions`.
+fn extension_fields(data: &[u8]) -> Result<Vec<(&'static str, Value)>, String> {
+    let mut fields = Vec::new();
+    let mut other = Vec::new();
+
+    for (extension, value) in extensions(data) {
+        if let Some(field) = metadata_field(extension, value) {
+            fields.push(field?);
+            continue;
+        }
+        let Ok(extension_type) = ExtensionType::try_from(extension) else {
+            other.push(json!(extension));
+            continue;
+        };
+        let field = match extension_type {
+            ExtensionType::TransferFeeConfig => {
+                pod_from_bytes::<TransferFeeConfig>(value).map(|config| {
+                    let fee = |fee: &TransferFee| {
+                        json!({
+                            "epoch": u64::from(fee.epoch),
+                            "maximum_fee": u64::from(fee.maximum_fee),
+                            "basis_points":
+                                u16::from(fee.transfer_fee_basis_points),
+                        })
+                    };
+                    (
+                        "transfer_fee_config",
+                        json!({
+                            "transfer_fee_config_authority":
+                                optional(config.transfer_fee_config_authority),
+                            "withdraw_withheld_authority":
+                                optional(config.withdraw_withheld_authority),
+                            "withheld_amount":
+                                u64::from(config.withheld_amount),
+                            "older_transfer_fee":
+                                fee(&config.older_transfer_fee),
+                            "newer_transfer_fee":
+                                fee(&config.newer_transfer_fee),
+                        }),
+                    )
+                })
+            }
+            ExtensionType::TransferFeeAmount => {
+                pod_from_bytes::<TransferFeeAmount>(value).map(|fees| {
+                    ("withheld_amount", json!(u64::from(fees.withheld_amount)))
+                })
+            }
+            ExtensionType::MintCloseAuthority => {
+                pod_from_bytes::<MintCloseAuthority>(value).map(|close| {
+                    ("mint_close_authority", optional(close.close_authority))
+                })
+            }
+            ExtensionType::InterestBearingConfig => {
+                pod_from_bytes::<InterestBearingConfig>(value).map(|config| {
+                    (
+                        "interest_bearing_config",
+                        json!({
+                            "rate_authority": optional(config.rate_authority),
+                            "current_rate": i16::from(config.current_rate),
+                            "pre_update_average_rate":
+                                i16::from(config.pre_update_average_rate),
+                            "initialization_timestamp":
+                                i64::from(config.initialization_timestamp),
+                            "last_update_timestamp":
+                                i64::from(config.last_update_timestamp),
+                        }),
+                    )
+                })
+            }
+            ExtensionType::DefaultAccountState => {
+                pod_from_bytes::<DefaultAccountState>(value).map(|default| {
+                    let state = AccountState::try_from(default.state)
+                        .map_or(json!(default.state), |state| {
+                            json!(format!("{state:?}"))
+                        });
+                    ("default_account_state", state)
+                })
+            }
+            ExtensionType::PermanentDelegate => {
+                pod_from_bytes::<PermanentDelegate>(value).map(|delegate| {
+                    ("permanent_delegate", optional(delegate.delegate))
+                })
+            }
+            ExtensionType::MemoTransfer => {
+                pod_from_bytes::<MemoTransfer>(value).map(|memo| {
+                    (
+                        "require_incoming_transfer_memos",
+                        json!(bool::from(memo.require_incoming_transfer_memos)),
+                    )
+                })
+            }
+            ExtensionType::CpiGuard => pod_from_bytes::<CpiGuard>(value)
+                .map(|guard| ("lock_cpi", json!(bool::from(guard.lock_cpi)))),
+            ExtensionType::NonTransferable => {
+                Ok(("non_transferable", json!(true)))
+            }
+            ExtensionType::NonTransferableAccount => {
+                Ok(("non_transferable", json!(true)))
+            }
+            ExtensionType::ImmutableOwner => {
+                Ok(("immutable_owner", json!(true)))
+            }
+            extension_type => {
+                other.push(json!(format!("{extension_type:?}")));
+                continue;
+            }
+        };
+        fields.push(field.map_err(|e| e.to_string())?);
+    }
+
+    if !other.is_empty() {
+        fields.push(("other_extensions", json!(other)));
+    }
+    Ok(fields)
+}
+
+fn decode_loader(data: &[u8]) -> Result<AccountData, String> {
+    let state: UpgradeableLoaderState =
+        bincode::deserialize(data).map_err(|e| e.to_string())?;
+
+    match state {
+        UpgradeableLoaderState::Program {
+            programdata_address,
+        } => Ok(AccountData::Program {
+            programdata_address,
+        }),
+        UpgradeableLoaderState::ProgramData {
+            slot,
+            upgrade_authority_address,
+        } => Ok(AccountData::ProgramData {
+            slot,
+            upgrade_authority_address,
+        }),
+        UpgradeableLoaderState::Buffer {
+            authority_address,
+        } => Ok(AccountData::Buffer {
+            authority_address,
+        }),
+        UpgradeableLoaderState::Uninitialized => {
+            Err("uninitialized loader account".to_string())
+        }
+    }
+}
+
+/// Decodes the metadata pointer and token metadata extensions, `None` for
+/// any other extension.
+fn metadata_field(
+    extension: u16,
+    value: &[u8],
+) -> Option<Result<(&'static str, Value), String>> {
+    let field = match extension {
+        METADATA_POINTER_EXTENSION => MetadataPointer::unpack(value)
+            .map(|pointer| {
+                (
+                    "metadata_pointer",
+                    json!({
+                        "authority": optional(pointer.authority),
+                        "metadata_address": optional(pointer.metadata_address),
+                    }),
+                )
+            })
+            .ok_or_else(|| "invalid metadata pointer".to_string()),
+        TOKEN_METADATA_EXTENSION => TokenMetadata::unpack(value)
+            .map(|metadata| {
+                (
+                    "token_metadata",
+                    json!({
+                        "update_authority": optional(metadata.update_authority),
+                        "mint": metadata.mint.to_string(),
+                        "name": metadata.name,
+                        "symbol": metadata.symbol,
+                        "uri": metadata.uri,
+                        "additional_metadata": metadata
+                            .additional_metadata
+                            .into_iter()
+                            .map(|(key, value)| (key, json!(value)))
+                            .collect::<serde_json::Map<_, _>>(),
+                    }),
+                )
+            })
+            .map_err(|e| format!("invalid token metadata: {e}")),
+        _ => return None,
+    };
+    Some(field)
+}
+
+fn optional<T: Into<Option<Pubkey>>>(pubkey: T) -> Value {
+    match pubkey.into() {
+        Some(pubkey) => json!(pubkey.to_string()),
+        None => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use spl_token_2022::extension::StateWithExtensionsMut;
+    use student_intro_program_mint::state::{
+        ReplyCounter, COUNTER_DISCRIMINATOR,
+    };
+
+    fn token_account(data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1,
+            data,
+            owner: spl_token_2022::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn loader_data(state: &UpgradeableLoaderState) -> Vec<u8> {
+        bincode::serialize(state).unwrap()
+    }
+
+    /// A Token-2022 mint with a close authority, followed by a metadata
+    /// pointer and an extension type unknown to the pinned spl-token-2022.
+    fn mint_with_extensions(
+        close_authority: Pubkey,
+        metadata_address: Pubkey,
+    ) -> Vec<u8> {
+        let len = ExtensionType::get_account_len::<Mint>(&[
+            ExtensionType::MintCloseAuthority,
+        ]);
+        let mut data = vec![0; len];
+        let mut state =
+            StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data)
+                .unwrap();
+        state
+            .init_extension::<MintCloseAuthority>(true)
+            .unwrap()
+            .close_authority = Some(close_authority).try_into().unwrap();
+        state.base = Mint {
+            supply: 42,
+            decimals: 6,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        data.extend(METADATA_POINTER_EXTENSION.to_le_bytes());
+        data.extend(64u16.to_le_bytes());
+        data.extend([0; 32]);
+        data.extend(metadata_address.to_bytes());
+        data.extend(200u16.to_le_bytes());
+        data.extend(2u16.to_le_bytes());
+        data.extend([0; 2]);
+        data
+    }
+
+    #[test]
+    fn decodes_token_2022_extensions() {
+        let close_authority = Pubkey::new_unique();
+        let metadata_address = Pubkey::new_unique();
+        let data = mint_with_extensions(close_authority, metadata_address);
+
+        assert_eq!(
+            extension_fields(&data).unwrap(),
+            [
+                ("mint_close_authority", json!(close_authority.to_string())),
+                (
+                    "metadata_pointer",
+                    json!({
+                        "authority": Value::Null,
+                        "metadata_address": metadata_address.to_string(),
+                    })
+                ),
+                ("other_extensions", json!([200])),
+            ]
+        );
+        match decode_data(&token_account(data), None) {
+            AccountData::Mint {
+                mint,
+                extensions,
+            } => {
+                assert_eq!(mint.supply, 42);
+                assert_eq!(mint.decimals, 6);
+                assert_eq!(extensions.len(), 3);
+            }
+            data => panic!("unexpected {data:?}"),
+        }
+    }
+
+    #[test]
+    fn decodes_token_accounts_by_size() {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(
+            TokenAccount {
+                mint,
+                amount: 7,
+                state: AccountState::Initialized,
+                ..TokenAccount::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        match decode_token(&data).unwrap() {
+            AccountData::TokenAccount {
+                account,
+                extensions,
+            } => {
+                assert_eq!(account.mint, mint);
+                assert_eq!(account.amount, 7);
+                assert!(extensions.is_empty());
+            }
+            data => panic!("unexpected {data:?}"),
+        }
+
+        let mut data = vec![0; Multisig::LEN];
+        let multisig = Multisig {
+            m: 1,
+            n: 1,
+            is_initialized: true,
+            ..Multisig::default()
+        };
+        Multisig::pack(multisig, &mut data).unwrap();
+        assert_eq!(decode_token(&data).unwrap().kind(), "Multisig");
+
+        assert_eq!(
+            decode_token(&[0; 10]).unwrap_err(),
+            "unexpected token account size 10"
+        );
+        let mut data = vec![0; TokenAccount::LEN + 1];
+        data[TokenAccount::LEN] = 9;
+        assert_eq!(
+            decode_token(&data).unwrap_err(),
+            "unknown token account type"
+        );
+    }
+
+    #[test]
+    fn decodes_loader_accounts() {
+        let programdata_address = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        match decode_loader(&loader_data(&UpgradeableLoaderState::Program {
+            programdata_address,
+        })) {
+            Ok(AccountData::Program {
+                programdata_address: address,
+            }) => assert_eq!(address, programdata_address),
+            data => panic!("unexpected {data:?}"),
+        }
+        match decode_loader(&loader_data(
+            &UpgradeableLoaderState::ProgramData {
+                slot: 5,
+                upgrade_authority_address: Some(authority),
+            },
+        )) {
+            Ok(AccountData::ProgramData {
+                slot,
+                upgrade_authority_address,
+            }) => {
+                assert_eq!(slot, 5);
+                assert_eq!(upgrade_authority_address, Some(authority));
+            }
+            data => panic!("unexpected {data:?}"),
+        }
+        match decode_loader(&loader_data(&UpgradeableLoaderState::Buffer {
+            authority_address: None,
+        })) {
+            Ok(AccountData::Buffer {
+                authority_address,
+            }) => assert_eq!(authority_address, None),
+            data => panic!("unexpected {data:?}"),
+        }
+        assert_eq!(
+            decode_loader(&loader_data(&UpgradeableLoaderState::Uninitialized))
+                .unwrap_err(),
+            "uninitialized loader account"
+        );
+        assert!(decode_loader(&[]).is_err());
+    }
+
+    #[test]
+    fn intro_accounts_need_the_program_id() {
+        let program_id = Pubkey::new_unique();
+        let counter = ReplyCounter {
+            discriminator: COUNTER_DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            counter: 3,
+        };
+        let account = Account {
+            owner: program_id,
+            ..token_account(counter.try_to_vec().unwrap())
+        };
+
+        assert_eq!(decode_data(&account, None).kind(), "Unknown");
+        assert_eq!(
+            decode_data(&account, Some(&program_id)).kind(),
+            "ReplyCounter"
+        );
+
+        let wallet = Account {
+            owner: system_program::id(),
+            ..token_account(Vec::new())
+        };
+        assert_eq!(decode_data(&wallet, None).kind(), "Unknown");
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    budget::{send_with_compute_budget, ComputeBudgetConfig},
+    query::IntroQuery,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::keypair::Keypair,
+    signer::Signer,
+    system_program,
+};
+
+/// Offset of the variant byte in a serialized [`StudentIntro`].
+pub const VARIANT_OFFSET: usize = 0;
+/// Offset of the `u32` length prefix of the name.
+pub const NAME_LEN_OFFSET: usize = VARIANT_OFFSET + 1;
+/// Offset of the name bytes.
+pub const NAME_OFFSET: usize = NAME_LEN_OFFSET + 4;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct StudentIntro {
+    pub variant: u8,
+    pub name: String,
+    pub message: String,
+}
+
+pub fn create_student_intro(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    payer: &Keypair,
+    name: &str,
+    message: &str,
+    budget: &ComputeBudgetConfig,
+) -> ClientResult<Signature> {
+    let instruction = create_student_intro_instruction(
+        program_id,
+        &payer.pubkey(),
+        name,
+        message,
+    );
+
+    send_with_compute_budget(
+        client,
+        &[instruction],
+        &payer.pubkey(),
+        &[payer],
+        budget,
+    )
+}
+
+/// Instruction creating the intro PDA of `payer`.
+pub fn create_student_intro_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let pda = Pubkey::find_program_address(&[payer.as_ref()], program_id).0;
+
+    let student_intro_instruction = StudentIntro {
+        variant: 0,
+        name: name.to_string(),
+        message: message.to_string(),
+    };
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &student_intro_instruction,
+        vec![
+            AccountMeta::new_readonly(*payer, true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Instruction replacing the name and message of `payer`'s intro.
+pub fn update_student_intro_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let pda = Pubkey::find_program_address(&[payer.as_ref()], program_id).0;
+
+    let student_intro_instruction = StudentIntro {
+        variant: 1,
+        name: name.to_string(),
+        message: message.to_string(),
+    };
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &student_intro_instruction,
+        vec![
+            AccountMeta::new_readonly(*payer, true),
+            AccountMeta::new(pda, false),
+        ],
+    )
+}
+
+pub fn get_student_intros(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    query: &IntroQuery,
+) -> ClientResult<Vec<(Pubkey, StudentIntro)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: query.rpc_filters(),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        with_context: None,
+    };
+
+    client
+        .get_program_accounts_with_config(program_id, config)?
+        .into_iter()
+        .map(|(pubkey, account)| {
+            let data = StudentIntro::deserialize(&mut account.data.as_slice())?;
+            Ok((pubkey, data))
+        })
+        .collect()
+}
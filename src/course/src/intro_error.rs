@@ -0,0 +1,94 @@
+use solana_client::client_error::ClientError;
+use solana_sdk::{
+    instruction::InstructionError, pubkey::Pubkey,
+    transaction::TransactionError,
+};
+use student_intro_program_mint::error::StudentIntroError;
+
+/// Describes a failed transaction, naming custom error codes after their
+/// [`StudentIntroError`] variant, e.g. `Instruction 2 failed: PDA derived
+/// does not equal PDA passed in (InvalidPDA)`.
+///
+/// `programs` are the programs of the transaction's top level instructions
+/// in order. Custom codes are only read as [`StudentIntroError`] when the
+/// failing instruction calls `intro_program_id`, other programs' codes are
+/// printed as they are.
+pub fn describe_transaction_error(
+    err: &TransactionError,
+    programs: &[Pubkey],
+    intro_program_id: &Pubkey,
+) -> String {
+    match err {
+        TransactionError::InstructionError(
+            index,
+            InstructionError::Custom(code),
+        ) => match StudentIntroError::try_from(*code) {
+            Ok(e)
+                if programs.get(usize::from(*index))
+                    == Some(intro_program_id) =>
+            {
+                format!("Instruction {index} failed: {e} ({e:?})")
+            }
+            _ => format!("Instruction {index} failed: custom error {code}"),
+        },
+        TransactionError::InstructionError(index, e) => {
+            format!("Instruction {index} failed: {e}")
+        }
+        err => err.to_string(),
+    }
+}
+
+/// Like [`describe_transaction_error`], falling back to the error itself when
+/// the transaction never ran.
+pub fn describe_client_error(
+    err: &ClientError,
+    programs: &[Pubkey],
+    intro_program_id: &Pubkey,
+) -> String {
+    match err.get_transaction_error() {
+        Some(err) => {
+            describe_transaction_error(&err, programs, intro_program_id)
+        }
+        None => err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::compute_budget;
+
+    #[test]
+    fn decodes_intro_program_codes() {
+        let intro = Pubkey::new_unique();
+        let programs = [compute_budget::id(), intro];
+        let err =
+            TransactionError::InstructionError(1, InstructionError::Custom(1));
+
+        assert_eq!(
+            describe_transaction_error(&err, &programs, &intro),
+            "Instruction 1 failed: PDA derived does not equal PDA passed in \
+             (InvalidPDA)"
+        );
+    }
+
+    #[test]
+    fn keeps_other_programs_codes() {
+        let intro = Pubkey::new_unique();
+        let programs = [spl_associated_token_account::id(), intro];
+        let err =
+            TransactionError::InstructionError(0, InstructionError::Custom(1));
+
+        assert_eq!(
+            describe_transaction_error(&err, &programs, &intro),
+            "Instruction 0 failed: custom error 1"
+        );
+        // An index past the known programs is not guessed at either.
+        let err =
+            TransactionError::InstructionError(5, InstructionError::Custom(1));
+        assert_eq!(
+            describe_transaction_error(&err, &programs, &intro),
+            "Instruction 5 failed: custom error 1"
+        );
+    }
+}
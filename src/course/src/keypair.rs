@@ -0,0 +1,271 @@
+use bip39::{Language, Mnemonic, Seed};
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    signer::keypair::{
+        keypair_from_seed, keypair_from_seed_and_derivation_path, Keypair,
+    },
+    signer::Signer,
+};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Environment variable the course clients historically read the secret
+/// key from.
+pub const PRIVATE_KEY_ENV: &str = "PRIVATE_KEY";
+
+#[derive(Debug, Error)]
+pub enum KeypairError {
+    #[error("failed to read keypair file {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("keypair file {path} is not a JSON byte array: {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[error("invalid byte `{0}` in keypair")]
+    InvalidByte(String),
+
+    #[error("invalid base58 keypair: {0}")]
+    Base58(#[from] bs58::decode::Error),
+
+    #[error("expected 64 keypair bytes, found {0}")]
+    InvalidLength(usize),
+
+    #[error("invalid keypair bytes: {0}")]
+    InvalidKeypair(String),
+
+    #[error("invalid seed phrase: {0}")]
+    InvalidSeedPhrase(String),
+
+    #[error("no keypair given: set {PRIVATE_KEY_ENV} or create {0}")]
+    NotFound(PathBuf),
+}
+
+/// `~/.config/solana/id.json`, the Solana CLI default keypair.
+pub fn default_keypair_path() -> PathBuf {
+    expand_tilde("~/.config/solana/id.json")
+}
+
+/// Loads the keypair from `PRIVATE_KEY` if it is set, falling back to the
+/// Solana CLI default keypair file.
+pub fn load_default_keypair() -> Result<Keypair, KeypairError> {
+    if let Ok(source) = std::env::var(PRIVATE_KEY_ENV) {
+        return load_keypair(&source, None);
+    }
+
+    let path = default_keypair_path();
+    if !path.exists() {
+        return Err(KeypairError::NotFound(path));
+    }
+    read_keypair_json(&path)
+}
+
+/// Loads a keypair from a JSON keypair file path, a `[1,2,...]` or `1,2,...`
+/// byte list, a BIP39 seed phrase or a base58-encoded secret key.
+///
+/// `derivation_path` only applies to seed phrases; without it the keypair is
+/// derived the same way as `solana-keygen recover`.
+pub fn load_keypair(
+    source: &str,
+    derivation_path: Option<DerivationPath>,
+) -> Result<Keypair, KeypairError> {
+    let source = source.trim();
+
+    if source.starts_with('[') || source.contains(',') {
+        keypair_from_byte_list(source)
+    } else if source.split_whitespace().nth(1).is_some() {
+        keypair_from_seed_phrase(source, "", derivation_path)
+    } else if is_path(source) {
+        read_keypair_json(expand_tilde(source))
+    } else {
+        keypair_from_base58(source)
+    }
+}
+
+/// Reads a Solana CLI style keypair file, a JSON array of 64 bytes.
+pub fn read_keypair_json<P: AsRef<Path>>(
+    path: P,
+) -> Result<Keypair, KeypairError> {
+    let path = path.as_ref();
+    let contents =
+        std::fs::read_to_string(path).map_err(|source| KeypairError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+    let bytes: Vec<u8> = serde_json::from_str(&contents).map_err(|source| {
+        KeypairError::Json {
+            path: path.to_path_buf(),
+            source,
+        }
+    })?;
+
+    keypair_from_bytes(&bytes)
+}
+
+/// Parses comma separated bytes, optionally wrapped in brackets.
+pub fn keypair_from_byte_list(list: &str) -> Result<Keypair, KeypairError> {
+    let bytes = list
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|byte| {
+            let byte = byte.trim();
+            byte.parse::<u8>()
+                .map_err(|_| KeypairError::InvalidByte(byte.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    keypair_from_bytes(&bytes)
+}
+
+pub fn keypair_from_base58(encoded: &str) -> Result<Keypair, KeypairError> {
+    let bytes = bs58::decode(encoded.trim()).into_vec()?;
+    keypair_from_bytes(&bytes)
+}
+
+pub fn keypair_from_seed_phrase(
+    phrase: &str,
+    passphrase: &str,
+    derivation_path: Option<DerivationPath>,
+) -> Result<Keypair, KeypairError> {
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mnemonic = Mnemonic::from_phrase(&phrase, Language::English)
+        .map_err(|e| KeypairError::InvalidSeedPhrase(e.to_string()))?;
+    let seed = Seed::new(&mnemonic, passphrase);
+
+    match derivation_path {
+        Some(path) => {
+            keypair_from_seed_and_derivation_path(seed.as_bytes(), Some(path))
+        }
+        None => keypair_from_seed(seed.as_bytes()),
+    }
+    .map_err(|e| KeypairError::InvalidKeypair(e.to_string()))
+}
+
+/// Drops repeated keypairs, a transaction takes each signer once even when
+/// it fills several roles.
+pub fn unique_signers<'a>(signers: &[&'a Keypair]) -> Vec<&'a Keypair> {
+    let mut unique: Vec<&Keypair> = Vec::with_capacity(signers.len());
+    for signer in signers {
+        if !unique.iter().any(|kept| kept.pubkey() == signer.pubkey()) {
+            unique.push(signer);
+        }
+    }
+    unique
+}
+
+fn keypair_from_bytes(bytes: &[u8]) -> Result<Keypair, KeypairError> {
+    if bytes.len() != 64 {
+        return Err(KeypairError::InvalidLength(bytes.len()));
+    }
+
+    Keypair::from_bytes(bytes)
+        .map_err(|e| KeypairError::InvalidKeypair(e.to_string()))
+}
+
+fn is_path(source: &str) -> bool {
+    source.contains(std::path::MAIN_SEPARATOR)
+        || source.ends_with(".json")
+        || Path::new(source).exists()
+}
+
+pub(crate) fn expand_tilde(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon \
+                          abandon abandon abandon abandon abandon about";
+
+    fn byte_list(keypair: &Keypair) -> String {
+        let bytes: Vec<_> =
+            keypair.to_bytes().iter().map(u8::to_string).collect();
+        bytes.join(",")
+    }
+
+    #[test]
+    fn loads_byte_lists_with_and_without_brackets() {
+        let keypair = Keypair::new();
+        let list = byte_list(&keypair);
+
+        for source in [format!(" [{list}] "), list] {
+            let loaded = load_keypair(&source, None).unwrap();
+            assert_eq!(loaded.pubkey(), keypair.pubkey());
+        }
+    }
+
+    #[test]
+    fn rejects_bad_bytes_and_lengths() {
+        assert!(matches!(
+            load_keypair("[1,2,300]", None),
+            Err(KeypairError::InvalidByte(byte)) if byte == "300"
+        ));
+        assert!(matches!(
+            load_keypair("[1,2,3]", None),
+            Err(KeypairError::InvalidLength(3))
+        ));
+    }
+
+    #[test]
+    fn loads_base58() {
+        let keypair = Keypair::new();
+        let loaded = load_keypair(&keypair.to_base58_string(), None).unwrap();
+        assert_eq!(loaded.pubkey(), keypair.pubkey());
+
+        assert!(matches!(
+            load_keypair("0OIl", None),
+            Err(KeypairError::Base58(_))
+        ));
+    }
+
+    #[test]
+    fn loads_json_files() {
+        let keypair = Keypair::new();
+        let dir = std::env::temp_dir();
+        let path =
+            dir.join(format!("course-keypair-{}.json", keypair.pubkey()));
+        std::fs::write(&path, format!("[{}]", byte_list(&keypair))).unwrap();
+
+        let loaded = load_keypair(path.to_str().unwrap(), None);
+        std::fs::write(&path, "not json").unwrap();
+        let invalid = read_keypair_json(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap().pubkey(), keypair.pubkey());
+        assert!(matches!(invalid, Err(KeypairError::Json { .. })));
+        assert!(matches!(
+            read_keypair_json(dir.join("course-missing-keypair.json")),
+            Err(KeypairError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn derives_seed_phrases() {
+        let keypair = load_keypair(PHRASE, None).unwrap();
+        let spaced = PHRASE.replace(' ', "  \n");
+        let respaced = load_keypair(&spaced, None).unwrap();
+        assert_eq!(respaced.pubkey(), keypair.pubkey());
+
+        let path = DerivationPath::new_bip44(Some(0), Some(0));
+        let derived = load_keypair(PHRASE, Some(path)).unwrap();
+        assert_ne!(derived.pubkey(), keypair.pubkey());
+
+        assert!(matches!(
+            load_keypair("abandon abandon", None),
+            Err(KeypairError::InvalidSeedPhrase(_))
+        ));
+    }
+}
@@ -0,0 +1,31 @@
+#![allow(clippy::result_large_err)]
+
+pub mod airdrop;
+pub mod budget;
+pub mod config;
+pub mod decode;
+pub mod export;
+pub mod fetch;
+pub mod history;
+pub mod inspect;
+pub mod intro;
+pub mod intro_error;
+pub mod keypair;
+pub mod logs;
+pub mod lookup_table;
+pub mod mint_intro;
+pub mod multisig;
+pub mod offline;
+pub mod paginate;
+pub mod payout;
+pub mod query;
+pub mod simulate;
+pub mod submit;
+pub mod token;
+pub mod token_2022;
+pub mod token_metadata;
+pub mod transfer;
+pub mod watch;
+
+pub const STUDENT_INTRO_PROGRAM_ID: &str =
+    "HdE95RSVsdb315jfJtaykXhXY478h53X6okDupVfY9yf";
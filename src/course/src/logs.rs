@@ -0,0 +1,390 @@
+use solana_sdk::pubkey::Pubkey;
+
+/// One program invocation reconstructed from transaction logs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invocation {
+    pub program_id: Pubkey,
+    /// 1 for instructions of the transaction, higher for CPIs.
+    pub depth: usize,
+    /// `msg!` output of this invocation, without the `Program log: ` prefix.
+    pub logs: Vec<String>,
+    pub consumed_units: Option<u64>,
+    /// `None` when the logs end before the invocation does, e.g. because
+    /// they were truncated.
+    pub result: Option<Result<(), String>>,
+    /// Programs invoked by this one, in order.
+    pub inner: Vec<Invocation>,
+}
+
+impl Invocation {
+    pub fn succeeded(&self) -> bool {
+        matches!(self.result, Some(Ok(())))
+    }
+
+    /// This invocation followed by every nested one, depth first.
+    pub fn iter(&self) -> impl Iterator<Item = &Invocation> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let invocation = stack.pop()?;
+            stack.extend(invocation.inner.iter().rev());
+            Some(invocation)
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntroEvent {
+    IntroCreated {
+        name: String,
+        message: String,
+        pda: Option<Pubkey>,
+    },
+    IntroUpdated {
+        name: String,
+        message: String,
+    },
+    ReplyAdded {
+        reply: String,
+        /// Replies on the intro including this one.
+        count: Option<u64>,
+    },
+    TokensMinted {
+        /// Whole tokens as logged by the program.
+        tokens: u64,
+    },
+    MintInitialized {
+        mint: Option<Pubkey>,
+        authority: Option<Pubkey>,
+    },
+}
+
+/// Rebuilds the invocation tree from a transaction's log messages, returning
+/// the top level instructions.
+pub fn parse_invocations<S: AsRef<str>>(logs: &[S]) -> Vec<Invocation> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<Invocation> = Vec::new();
+
+    for line in logs {
+        let line = line.as_ref();
+
+        if let Some(log) = line.strip_prefix("Program log: ") {
+            if let Some(current) = stack.last_mut() {
+                current.logs.push(log.to_string());
+            }
+            continue;
+        }
+
+        let Some((program_id, rest)) =
+            line.strip_prefix("Program ").and_then(|line| line.split_once(' '))
+        else {
+            continue;
+        };
+        let Ok(program_id) = program_id.parse::<Pubkey>() else {
+            continue;
+        };
+
+        if let Some(depth) = rest
+            .strip_prefix("invoke [")
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            stack.push(Invocation {
+                program_id,
+                depth: depth.parse().unwrap_or(stack.len() + 1),
+                logs: Vec::new(),
+                consumed_units: None,
+                result: None,
+                inner: Vec::new(),
+            });
+        } else if let Some(units) = rest.strip_prefix("consumed ") {
+            if let Some(current) = stack.last_mut() {
+                current.consumed_units = units
+                    .split_once(' ')
+                    .and_then(|(units, _)| units.parse().ok());
+            }
+        } else if rest == "success" || rest.starts_with("failed: ") {
+            let Some(mut invocation) = stack.pop() else {
+                continue;
+            };
+            invocation.result = match rest.strip_prefix("failed: ") {
+                Some(reason) => Some(Err(reason.to_string())),
+                None => Some(Ok(())),
+            };
+            close(invocation, &mut stack, &mut roots);
+        }
+    }
+
+    while let Some(invocation) = stack.pop() {
+        close(invocation, &mut stack, &mut roots);
+    }
+
+    roots
+}
+
+/// Events logged by successful invocations of `program_id`, at any depth.
+pub fn parse_intro_events<S: AsRef<str>>(
+    logs: &[S],
+    program_id: &Pubkey,
+) -> Vec<IntroEvent> {
+    parse_invocations(logs)
+        .iter()
+        .flat_map(Invocation::iter)
+        .filter(|invocation| {
+            invocation.program_id == *program_id && invocation.succeeded()
+        })
+        .flat_map(|invocation| intro_events(&invocation.logs))
+        .collect()
+}
+
+/// Events described by the `msg!` output of a single invocation.
+pub fn intro_events<S: AsRef<str>>(logs: &[S]) -> Vec<IntroEvent> {
+    let mut events = Vec::new();
+    let mut lines = logs.iter().map(AsRef::as_ref).peekable();
+
+    while let Some(line) = lines.next() {
+        match line {
+            "Adding student intro..." | "Updating student intro..." => {
+                let name = field(lines.next(), "Name: ");
+                let message = field(lines.next(), "Message: ");
+                let (Some(name), Some(message)) = (name, message) else {
+                    continue;
+                };
+
+                if line == "Updating student intro..." {
+                    events.push(IntroEvent::IntroUpdated {
+                        name,
+                        message,
+                    });
+                    continue;
+                }
+
+                // The PDA is logged further down, after any token minting.
+                let pda = logs
+                    .iter()
+                    .filter_map(|line| {
+                        field(Some(line.as_ref()), "PDA created: ")
+                    })
+                    .find_map(|pda| pda.parse().ok());
+                events.push(IntroEvent::IntroCreated {
+                    name,
+                    message,
+                    pda,
+                });
+            }
+            "Adding Reply..." => {
+                let Some(reply) = field(lines.next(), "Reply: ") else {
+                    continue;
+                };
+                let count = match lines.peek() {
+                    Some(line) => field(Some(line), "Reply Count: ")
+                        .and_then(|count| count.parse().ok()),
+                    None => None,
+                };
+                events.push(IntroEvent::ReplyAdded {
+                    reply,
+                    count,
+                });
+            }
+            "Initialized token mint" => {
+                let find = |prefix| {
+                    logs.iter()
+                        .filter_map(|line| field(Some(line.as_ref()), prefix))
+                        .find_map(|key| key.parse().ok())
+                };
+                events.push(IntroEvent::MintInitialized {
+                    mint: find("Token mint: "),
+                    authority: find("Mint authority: "),
+                });
+            }
+            _ => {
+                let Some(tokens) = line
+                    .strip_prefix("Minting ")
+                    .and_then(|rest| rest.split_once(" tokens"))
+                    .and_then(|(tokens, _)| tokens.parse().ok())
+                else {
+                    continue;
+                };
+                if lines.peek() == Some(&"Tokens minted") {
+                    events.push(IntroEvent::TokensMinted {
+                        tokens,
+                    });
+                }
+            }
+        }
+    }
+
+    events
+}
+
+fn field(line: Option<&str>, prefix: &str) -> Option<String> {
+    line?.strip_prefix(prefix).map(str::to_string)
+}
+
+fn close(
+    invocation: Invocation,
+    stack: &mut [Invocation],
+    roots: &mut Vec<Invocation>,
+) {
+    match stack.last_mut() {
+        Some(parent) => parent.inner.push(invocation),
+        None => roots.push(invocation),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM_ID: &str = "HdE95RSVsdb315jfJtaykXhXY478h53X6okDupVfY9yf";
+    const TOKEN_PROGRAM_ID: &str =
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+    const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+
+    /// Logs of the mint-enabled program adding an intro, as written by its
+    /// `msg!` calls.
+    fn add_intro_logs(pda: &Pubkey) -> Vec<String> {
+        vec![
+            format!("Program {PROGRAM_ID} invoke [1]"),
+            "Program log: Adding student intro...".to_string(),
+            "Program log: Name: Ada".to_string(),
+            "Program log: Message: hello".to_string(),
+            "Program log: Minting 10 tokens to User associated token account"
+                .to_string(),
+            format!("Program {TOKEN_PROGRAM_ID} invoke [2]"),
+            "Program log: Instruction: MintTo".to_string(),
+            format!(
+                "Program {TOKEN_PROGRAM_ID} consumed 4492 of 180000 compute \
+                 units"
+            ),
+            format!("Program {TOKEN_PROGRAM_ID} success"),
+            "Program log: Tokens minted".to_string(),
+            format!("Program {SYSTEM_PROGRAM_ID} invoke [2]"),
+            format!("Program {SYSTEM_PROGRAM_ID} success"),
+            format!("Program log: PDA created: {pda}"),
+            format!(
+                "Program {PROGRAM_ID} consumed 31000 of 200000 compute units"
+            ),
+            format!("Program {PROGRAM_ID} success"),
+        ]
+    }
+
+    #[test]
+    fn rebuilds_the_invocation_tree() {
+        let invocations =
+            parse_invocations(&add_intro_logs(&Pubkey::new_unique()));
+
+        assert_eq!(invocations.len(), 1);
+        let root = &invocations[0];
+        assert_eq!(root.program_id, PROGRAM_ID.parse().unwrap());
+        assert_eq!(root.depth, 1);
+        assert_eq!(root.consumed_units, Some(31000));
+        assert!(root.succeeded());
+        assert_eq!(root.logs.len(), 6);
+
+        let inner: Vec<_> =
+            root.inner.iter().map(|invocation| invocation.program_id).collect();
+        assert_eq!(
+            inner,
+            [
+                TOKEN_PROGRAM_ID.parse().unwrap(),
+                SYSTEM_PROGRAM_ID.parse().unwrap()
+            ]
+        );
+        assert_eq!(root.inner[0].logs, ["Instruction: MintTo"]);
+        assert_eq!(root.inner[0].consumed_units, Some(4492));
+        assert_eq!(root.iter().count(), 3);
+    }
+
+    #[test]
+    fn failures_and_truncated_logs() {
+        let logs = [
+            format!("Program {PROGRAM_ID} invoke [1]"),
+            "Program log: Invalid seeds for PDA".to_string(),
+            format!("Program {PROGRAM_ID} failed: custom program error: 0x2"),
+            format!("Program {PROGRAM_ID} invoke [1]"),
+            "Log truncated".to_string(),
+        ];
+        let invocations = parse_invocations(&logs);
+
+        assert_eq!(invocations.len(), 2);
+        assert_eq!(
+            invocations[0].result,
+            Some(Err("custom program error: 0x2".to_string()))
+        );
+        assert_eq!(invocations[1].result, None);
+    }
+
+    #[test]
+    fn parses_intro_events() {
+        let pda = Pubkey::new_unique();
+        let events = parse_intro_events(
+            &add_intro_logs(&pda),
+            &PROGRAM_ID.parse().unwrap(),
+        );
+
+        assert_eq!(
+            events,
+            [
+                IntroEvent::IntroCreated {
+                    name: "Ada".to_string(),
+                    message: "hello".to_string(),
+                    pda: Some(pda),
+                },
+                IntroEvent::TokensMinted {
+                    tokens: 10,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_updates_replies_and_mint_initialization() {
+        let mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let logs = [
+            "Updating student intro...".to_string(),
+            "Name: Ada".to_string(),
+            "Message: bye".to_string(),
+            "Adding Reply...".to_string(),
+            "Reply: nice".to_string(),
+            "Reply Count: 3".to_string(),
+            format!("Token mint: {mint:?}"),
+            format!("Mint authority: {authority:?}"),
+            "Created token mint account".to_string(),
+            "Initialized token mint".to_string(),
+        ];
+
+        assert_eq!(
+            intro_events(&logs),
+            [
+                IntroEvent::IntroUpdated {
+                    name: "Ada".to_string(),
+                    message: "bye".to_string(),
+                },
+                IntroEvent::ReplyAdded {
+                    reply: "nice".to_string(),
+                    count: Some(3),
+                },
+                IntroEvent::MintInitialized {
+                    mint: Some(mint),
+                    authority: Some(authority),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_events_of_failed_and_other_programs() {
+        let mut logs = add_intro_logs(&Pubkey::new_unique());
+        let last = logs.len() - 1;
+        logs[last] =
+            format!("Program {PROGRAM_ID} failed: custom program error: 0x1");
+
+        let program_id = PROGRAM_ID.parse().unwrap();
+        assert!(parse_intro_events(&logs, &program_id).is_empty());
+        assert!(parse_intro_events(
+            &add_intro_logs(&Pubkey::new_unique()),
+            &Pubkey::new_unique()
+        )
+        .is_empty());
+    }
+}
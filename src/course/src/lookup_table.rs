@@ -0,0 +1,128 @@
+use crate::{
+    budget::{send_with_compute_budget, ComputeBudgetConfig},
+    keypair::unique_signers,
+};
+use solana_address_lookup_table_program::{
+    instruction::{create_lookup_table, extend_lookup_table},
+    state::AddressLookupTable,
+};
+use solana_client::{
+    client_error::{ClientErrorKind, Result as ClientResult},
+    rpc_client::RpcClient,
+};
+use solana_sdk::{
+    address_lookup_table_account::AddressLookupTableAccount,
+    instruction::Instruction, pubkey::Pubkey, signer::keypair::Keypair,
+    signer::Signer,
+};
+
+/// Addresses appended per `ExtendLookupTable` transaction, small enough to
+/// stay well within the packet size.
+const MAX_EXTEND_ADDRESSES: usize = 20;
+
+/// Creates a lookup table owned by `authority` holding `addresses`.
+///
+/// The table only resolves addresses from the slot after it was extended, so
+/// wait for confirmation before referencing it.
+pub fn create_address_lookup_table(
+    client: &RpcClient,
+    payer: &Keypair,
+    authority: &Keypair,
+    addresses: &[Pubkey],
+    budget: &ComputeBudgetConfig,
+) -> ClientResult<Pubkey> {
+    let (table, instructions) = create_lookup_table_instructions(
+        client,
+        &payer.pubkey(),
+        &authority.pubkey(),
+        addresses,
+    )?;
+    send_with_compute_budget(
+        client,
+        &instructions,
+        &payer.pubkey(),
+        &unique_signers(&[payer, authority]),
+        budget,
+    )?;
+
+    if let Some(rest) = addresses.get(MAX_EXTEND_ADDRESSES..) {
+        extend_address_lookup_table(
+            client, payer, authority, &table, rest, budget,
+        )?;
+    }
+
+    Ok(table)
+}
+
+/// Address of a new lookup table and the instructions creating it with the
+/// first chunk of `addresses`. The rest are appended by later transactions,
+/// once the table exists.
+pub fn create_lookup_table_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    addresses: &[Pubkey],
+) -> ClientResult<(Pubkey, Vec<Instruction>)> {
+    let recent_slot = client.get_slot()?;
+    let (create_instruction, table) =
+        create_lookup_table(*authority, *payer, recent_slot);
+
+    let mut instructions = vec![create_instruction];
+    if let Some(chunk) = addresses.chunks(MAX_EXTEND_ADDRESSES).next() {
+        instructions.push(extend_lookup_table(
+            table,
+            *authority,
+            Some(*payer),
+            chunk.to_vec(),
+        ));
+    }
+
+    Ok((table, instructions))
+}
+
+/// Appends `addresses` to an existing lookup table.
+pub fn extend_address_lookup_table(
+    client: &RpcClient,
+    payer: &Keypair,
+    authority: &Keypair,
+    table: &Pubkey,
+    addresses: &[Pubkey],
+    budget: &ComputeBudgetConfig,
+) -> ClientResult<()> {
+    for chunk in addresses.chunks(MAX_EXTEND_ADDRESSES) {
+        let instruction = extend_lookup_table(
+            *table,
+            authority.pubkey(),
+            Some(payer.pubkey()),
+            chunk.to_vec(),
+        );
+        send_with_compute_budget(
+            client,
+            &[instruction],
+            &payer.pubkey(),
+            &unique_signers(&[payer, authority]),
+            budget,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Loads a lookup table so it can be passed to a v0 message.
+pub fn get_address_lookup_table(
+    client: &RpcClient,
+    table: &Pubkey,
+) -> ClientResult<AddressLookupTableAccount> {
+    let account = client.get_account(table)?;
+    let lookup_table =
+        AddressLookupTable::deserialize(&account.data).map_err(|e| {
+            ClientErrorKind::Custom(format!(
+                "{table} is not an address lookup table: {e}"
+            ))
+        })?;
+
+    Ok(AddressLookupTableAccount {
+        key: *table,
+        addresses: lookup_table.addresses.to_vec(),
+    })
+}
@@ -0,0 +1,2347 @@
+use clap::{Args, Parser, Subcommand};
+use course::{
+    airdrop::ensure_funded,
+    budget::{
+        budgeted_program_ids, estimate_versioned_compute_budget,
+        ComputeBudgetConfig,
+    },
+    config::{
+        self, Cluster, Profile, STUDENT_INTRO_MINT_PROGRAM,
+        STUDENT_INTRO_PROGRAM,
+    },
+    decode::{decode_accounts, IntroAccount},
+    export::{export_intros, ExportFormat},
+    fetch::FetchConfig,
+    history::{self, IntroCall},
+    inspect::{inspect, Inspection},
+    intro,
+    intro_error::describe_client_error,
+    keypair,
+    logs::parse_intro_events,
+    lookup_table::{
+        create_address_lookup_table, create_lookup_table_instructions,
+        get_address_lookup_table,
+    },
+    mint_intro,
+    multisig::{self, MultisigStatus},
+    offline::{self, TransactionEncoding},
+    paginate::{Cursor, IntroPaginator},
+    payout::{self, BatchOutcome, Journal},
+    query::IntroQuery,
+    simulate::{
+        simulate, simulate_signed_transaction, simulate_versioned, Simulation,
+    },
+    submit::{submit_with_compute_budget, SubmitConfig, SubmitOutcome},
+    token,
+    token_2022::{self, MintConfig, TransferFee},
+    token_metadata::{self, Field, MintMetadata, TokenMetadata},
+    transfer,
+    watch::{ChangeKind, IntroUpdate, IntroWatcher, WatchConfig},
+};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient as NonblockingRpcClient,
+    rpc_client::RpcClient,
+};
+use solana_sdk::{
+    account::Account,
+    address_lookup_table_account::AddressLookupTableAccount,
+    commitment_config::CommitmentConfig,
+    derivation_path::DerivationPath,
+    instruction::Instruction,
+    native_token::{lamports_to_sol, sol_to_lamports},
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{keypair::Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_token_2022::{instruction::AuthorityType, state::AccountState};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::ControlFlow,
+    path::PathBuf,
+};
+use student_intro_program_mint::instruction::IntroInstruction;
+
+#[derive(Parser)]
+#[clap(name = "course", about = "Solana development course client")]
+struct Cli {
+    /// Profile from `~/.config/solana-course/config.toml` to use instead of
+    /// the active one
+    #[clap(long, global = true)]
+    profile: Option<String>,
+
+    /// RPC endpoint of the cluster, overrides the profile
+    #[clap(long, global = true)]
+    url: Option<String>,
+
+    /// Keypair file, base58 secret key or seed phrase used to sign
+    /// transactions, defaults to the profile's keypair, `PRIVATE_KEY` or
+    /// `~/.config/solana/id.json`
+    #[clap(long, global = true)]
+    keypair: Option<String>,
+
+    /// Derivation path used when the keypair is a seed phrase, e.g.
+    /// `m/44'/501'/0'/0'`
+    #[clap(long, global = true)]
+    derivation_path: Option<String>,
+
+    /// Student intro program id, overrides the profile
+    #[clap(long, global = true)]
+    program_id: Option<Pubkey>,
+
+    /// Mint-enabled student intro program id used by `intro create-minted`
+    /// and `intro create-lookup-table`, overrides the profile
+    #[clap(long, global = true)]
+    mint_program_id: Option<Pubkey>,
+
+    /// Commitment level used for RPC requests, overrides the profile
+    #[clap(long, global = true)]
+    commitment: Option<CommitmentConfig>,
+
+    /// Most lamports paid in priority fees per transaction, 0 disables
+    /// priority fees
+    #[clap(long, global = true, default_value_t = 100_000)]
+    max_priority_fee: u64,
+
+    /// Percentile of recent prioritization fees to pay
+    #[clap(long, global = true, default_value_t = 50)]
+    priority_fee_percentile: u8,
+
+    /// Extra compute units requested on top of the simulated usage, in
+    /// percent
+    #[clap(long, global = true, default_value_t = 10)]
+    compute_unit_margin: u32,
+
+    /// Times a transaction is signed again with a fresh blockhash after the
+    /// previous one expired
+    #[clap(long, global = true, default_value_t = 2)]
+    max_resigns: u32,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+impl Cli {
+    /// The selected profile with the command line overrides applied.
+    fn profile(&self) -> anyhow::Result<Profile> {
+        let mut profile = config::load_profile(self.profile.as_deref())?;
+        if let Some(url) = &self.url {
+            profile.set_rpc_url(url);
+        }
+        if let Some(keypair) = &self.keypair {
+            profile.keypair = Some(keypair.clone());
+        }
+        if let Some(program_id) = self.program_id {
+            profile
+                .program_ids
+                .insert(STUDENT_INTRO_PROGRAM.to_string(), program_id);
+        }
+        if let Some(program_id) = self.mint_program_id {
+            profile
+                .program_ids
+                .insert(STUDENT_INTRO_MINT_PROGRAM.to_string(), program_id);
+        }
+        if let Some(commitment) = self.commitment {
+            profile.commitment = commitment;
+        }
+        Ok(profile)
+    }
+
+    fn compute_budget(&self) -> ComputeBudgetConfig {
+        ComputeBudgetConfig {
+            unit_margin_percent: self.compute_unit_margin,
+            fee_percentile: self.priority_fee_percentile,
+            max_priority_fee: self.max_priority_fee,
+            ..ComputeBudgetConfig::default()
+        }
+    }
+
+    fn submit_config(&self) -> SubmitConfig {
+        SubmitConfig {
+            max_resigns: self.max_resigns,
+            ..SubmitConfig::default()
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the SOL balance of an account
+    Balance {
+        pubkey: Pubkey,
+    },
+    /// Print whether an account is executable
+    Account {
+        pubkey: Pubkey,
+    },
+    /// Print an account's owner, balance and size, decoding its data when
+    /// the owner is a known program
+    Inspect {
+        pubkey: Pubkey,
+        /// Print JSON instead of a table
+        #[clap(long)]
+        json: bool,
+    },
+    /// Airdrop SOL until an account holds at least `amount` SOL
+    Airdrop {
+        amount: f64,
+        /// Account to fund, defaults to the keypair
+        #[clap(long)]
+        to: Option<Pubkey>,
+    },
+    /// Transfer SOL from the keypair to a recipient
+    Transfer {
+        to: Pubkey,
+        amount: f64,
+        /// Simulate the transaction and print its logs and account changes
+        /// instead of sending it
+        #[clap(long)]
+        simulate: bool,
+    },
+    /// Pay SOL to every `recipient,amount_sol` row of a CSV manifest
+    Payout {
+        manifest: PathBuf,
+        /// Progress journal, defaults to the manifest path with `.journal`
+        /// appended
+        #[clap(long)]
+        journal: Option<PathBuf>,
+        /// Print the total cost, fees included, without sending anything
+        #[clap(long)]
+        dry_run: bool,
+        /// Simulate every unpaid batch and print its logs and account
+        /// changes instead of sending it
+        #[clap(long)]
+        simulate: bool,
+    },
+    /// Interact with the student intro program
+    #[clap(subcommand)]
+    Intro(IntroCommand),
+    /// Manage durable nonce accounts
+    #[clap(subcommand)]
+    Nonce(NonceCommand),
+    /// Build transactions against a durable nonce, sign them offline and
+    /// broadcast them later
+    #[clap(subcommand)]
+    Offline(OfflineCommand),
+    /// Create and manage SPL tokens, the keypair pays and signs as the
+    /// owner or authority
+    Token {
+        /// Simulate the transactions and print their logs and account
+        /// changes instead of sending them
+        #[clap(long, global = true)]
+        simulate: bool,
+        #[clap(subcommand)]
+        command: TokenCommand,
+    },
+    /// Print student intros as they are created or changed
+    Watch {
+        /// Websocket endpoint, defaults to the profile's
+        #[clap(long)]
+        ws_url: Option<String>,
+        /// Also print the intros that exist when the watch starts
+        #[clap(long)]
+        existing: bool,
+        /// Print one JSON object per update instead of text
+        #[clap(long)]
+        json: bool,
+        /// Give up after this many consecutive failed connection attempts
+        #[clap(long)]
+        max_retries: Option<u32>,
+        #[clap(flatten)]
+        query: QueryArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum IntroCommand {
+    /// Create a student intro for the keypair
+    Create {
+        #[clap(long)]
+        name: String,
+        #[clap(long)]
+        message: String,
+        /// Simulate the transaction and print its logs and account changes
+        /// instead of sending it
+        #[clap(long)]
+        simulate: bool,
+    },
+    /// Create a student intro with the mint-enabled program, which also
+    /// mints reward tokens to the keypair
+    CreateMinted {
+        #[clap(long)]
+        name: String,
+        #[clap(long)]
+        message: String,
+        /// Address lookup table holding the program's static accounts, sends
+        /// a legacy transaction when omitted
+        #[clap(long)]
+        lookup_table: Option<Pubkey>,
+        /// Simulate the transaction and print its logs and account changes
+        /// instead of sending it
+        #[clap(long)]
+        simulate: bool,
+    },
+    /// Create the mint-enabled program's reward mint with a name, symbol and
+    /// URI, the keypair becomes the update authority of its metadata
+    InitMetadataMint {
+        #[clap(long)]
+        name: String,
+        #[clap(long)]
+        symbol: String,
+        #[clap(long)]
+        uri: String,
+    },
+    /// Set a metadata field of the mint-enabled program's reward mint, the
+    /// keypair must be its update authority
+    SetMintMetadata {
+        /// `name`, `symbol`, `uri` or any additional key
+        field: String,
+        value: String,
+    },
+    /// Create an address lookup table holding the static accounts of the
+    /// mint-enabled program
+    CreateLookupTable {
+        /// Simulate the transaction creating the table and print its logs
+        /// and account changes instead of sending it
+        #[clap(long)]
+        simulate: bool,
+    },
+    /// List every student intro
+    List {
+        #[clap(flatten)]
+        query: QueryArgs,
+    },
+    /// Print a single page of student intros sorted by name
+    Page {
+        #[clap(default_value_t = 1)]
+        page: usize,
+        #[clap(long, default_value_t = 10)]
+        per_page: usize,
+        /// Print the page following this cursor instead of `page`
+        #[clap(long)]
+        after: Option<Cursor>,
+        /// Number of account requests sent concurrently
+        #[clap(long, default_value_t = 4)]
+        concurrency: usize,
+        #[clap(flatten)]
+        query: QueryArgs,
+    },
+    /// Decode every account owned by the program, reporting the ones that
+    /// fail instead of stopping
+    Decode,
+    /// Print the program's instructions from its recent transactions,
+    /// newest first
+    History {
+        /// Number of transactions to fetch, at most 1000
+        #[clap(long, default_value_t = 20)]
+        limit: usize,
+        /// Print the transactions older than this signature
+        #[clap(long)]
+        before: Option<Signature>,
+        /// Print one JSON object per instruction instead of text
+        #[clap(long)]
+        json: bool,
+    },
+    /// Export every student intro as CSV, JSON or NDJSON
+    Export {
+        /// Output format: csv, json or ndjson
+        #[clap(long, default_value = "csv")]
+        format: ExportFormat,
+        /// File to write to instead of stdout
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+        /// Number of account requests sent concurrently
+        #[clap(long, default_value_t = 4)]
+        concurrency: usize,
+        #[clap(flatten)]
+        query: QueryArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokenCommand {
+    /// Create a mint, with the keypair as mint authority unless another one
+    /// is given
+    CreateMint {
+        #[clap(long, default_value_t = 9)]
+        decimals: u8,
+        /// Mint authority, such as a multisig, defaults to the keypair
+        #[clap(long, conflicts_with = "name")]
+        mint_authority: Option<Pubkey>,
+        /// Freeze authority, the mint has none when omitted
+        #[clap(long)]
+        freeze_authority: Option<Pubkey>,
+        /// Create the mint with Token-2022, required by the extension flags
+        #[clap(long)]
+        token_2022: bool,
+        /// Withhold this share of every transfer, in basis points. The
+        /// keypair can withdraw the fees.
+        #[clap(long, requires = "token-2022")]
+        transfer_fee_bps: Option<u16>,
+        /// Cap on the fee of a single transfer, in base units
+        #[clap(long, requires = "transfer-fee-bps", default_value_t = u64::MAX)]
+        max_fee: u64,
+        /// Authority allowed to close the mint once its supply is zero
+        #[clap(long, requires = "token-2022")]
+        close_authority: Option<Pubkey>,
+        /// Annual interest rate in basis points, updatable by the keypair
+        #[clap(long, requires = "token-2022", allow_hyphen_values = true)]
+        interest_rate: Option<i16>,
+        /// Create new token accounts frozen until the freeze authority
+        /// thaws them
+        #[clap(long, requires_all = &["token-2022", "freeze-authority"])]
+        default_frozen: bool,
+        /// Forbid transferring the tokens once minted
+        #[clap(long, requires = "token-2022")]
+        non_transferable: bool,
+        /// Name stored in the mint's metadata, updatable by the keypair
+        #[clap(long, requires_all = &["token-2022", "symbol"])]
+        name: Option<String>,
+        /// Symbol stored in the mint's metadata
+        #[clap(long, requires = "name")]
+        symbol: Option<String>,
+        /// URI of the mint's off-chain metadata JSON
+        #[clap(long, requires = "name", default_value = "")]
+        uri: String,
+    },
+    /// Create an m-of-n multisig to use as a mint, freeze or owner
+    /// authority
+    CreateMultisig {
+        /// Number of signatures required
+        #[clap(long)]
+        m: u8,
+        /// Members of the multisig, at most 11
+        #[clap(required = true)]
+        signers: Vec<Pubkey>,
+        /// Create the multisig with Token-2022, for Token-2022 mints
+        #[clap(long)]
+        token_2022: bool,
+    },
+    /// Create the associated token account of a wallet if it is missing
+    CreateAccount {
+        mint: Pubkey,
+        /// Wallet owning the tokens, defaults to the keypair
+        #[clap(long)]
+        owner: Option<Pubkey>,
+        /// Create an auxiliary token account at a new address instead
+        #[clap(long)]
+        auxiliary: bool,
+    },
+    /// Mint tokens to a wallet's associated token account
+    Mint {
+        mint: Pubkey,
+        /// Amount in base units
+        amount: u64,
+        /// Wallet receiving the tokens, defaults to the keypair
+        #[clap(long)]
+        to: Option<Pubkey>,
+        /// Token account receiving the tokens instead of an associated
+        /// token account
+        #[clap(long, conflicts_with = "to")]
+        to_account: Option<Pubkey>,
+    },
+    /// Transfer tokens to a wallet's associated token account
+    Transfer {
+        mint: Pubkey,
+        /// Amount in base units
+        amount: u64,
+        /// Wallet receiving the tokens
+        #[clap(long, required_unless_present = "to-account")]
+        to: Option<Pubkey>,
+        /// Token account receiving the tokens instead of an associated
+        /// token account
+        #[clap(long, conflicts_with = "to")]
+        to_account: Option<Pubkey>,
+        /// Token account to send from, defaults to the keypair's
+        /// associated token account
+        #[clap(long)]
+        from: Option<Pubkey>,
+    },
+    /// Burn tokens
+    Burn {
+        mint: Pubkey,
+        /// Amount in base units
+        amount: u64,
+        #[clap(flatten)]
+        account: TokenAccountArg,
+    },
+    /// Let a delegate transfer or burn tokens
+    Approve {
+        mint: Pubkey,
+        delegate: Pubkey,
+        /// Amount in base units
+        amount: u64,
+        #[clap(flatten)]
+        account: TokenAccountArg,
+    },
+    /// Remove the delegate of a token account
+    Revoke {
+        mint: Pubkey,
+        #[clap(flatten)]
+        account: TokenAccountArg,
+    },
+    /// Close an empty token account and reclaim its rent
+    Close {
+        mint: Pubkey,
+        /// Account receiving the lamports, defaults to the keypair
+        #[clap(long)]
+        destination: Option<Pubkey>,
+        #[clap(flatten)]
+        account: TokenAccountArg,
+    },
+    /// Update the balance of a wrapped SOL account after SOL was sent to it
+    SyncNative {
+        #[clap(flatten)]
+        account: TokenAccountArg,
+    },
+    /// Print the metadata stored in a Token-2022 mint
+    Metadata {
+        mint: Pubkey,
+    },
+    /// Set a metadata field of a Token-2022 mint, the keypair must be the
+    /// update authority
+    SetMetadata {
+        mint: Pubkey,
+        /// `name`, `symbol`, `uri` or any additional key
+        field: Field,
+        value: String,
+    },
+    /// Move the transfer fees withheld in a Token-2022 mint's accounts to
+    /// the mint
+    HarvestFees {
+        mint: Pubkey,
+    },
+    /// Collect the transfer fees withheld in a Token-2022 mint and its
+    /// accounts, the keypair must be the withdraw authority
+    WithdrawFees {
+        mint: Pubkey,
+        #[clap(flatten)]
+        account: TokenAccountArg,
+    },
+}
+
+#[derive(Args)]
+struct TokenAccountArg {
+    /// Token account to use, defaults to the keypair's associated token
+    /// account
+    #[clap(long)]
+    account: Option<Pubkey>,
+}
+
+impl TokenAccountArg {
+    fn resolve(
+        &self,
+        client: &RpcClient,
+        owner: &Pubkey,
+        mint: &Pubkey,
+    ) -> anyhow::Result<Pubkey> {
+        match self.account {
+            Some(account) => Ok(account),
+            None => Ok(token::associated_token_address(
+                owner,
+                mint,
+                &token::token_program(client, mint)?,
+            )),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum NonceCommand {
+    /// Create a nonce account, paid for by the keypair
+    Create {
+        /// Nonce authority, defaults to the keypair
+        #[clap(long)]
+        authority: Option<Pubkey>,
+        /// Simulate the transaction and print its logs and account changes
+        /// instead of sending it
+        #[clap(long)]
+        simulate: bool,
+    },
+    /// Print the stored nonce and its authority
+    Show {
+        nonce_account: Pubkey,
+    },
+    /// Replace the stored nonce, the keypair must be the nonce authority
+    Advance {
+        nonce_account: Pubkey,
+        /// Simulate the transaction and print its logs and account changes
+        /// instead of sending it
+        #[clap(long)]
+        simulate: bool,
+    },
+    /// Withdraw SOL from a nonce account, the keypair must be the nonce
+    /// authority
+    Withdraw {
+        nonce_account: Pubkey,
+        to: Pubkey,
+        amount: f64,
+        /// Simulate the transaction and print its logs and account changes
+        /// instead of sending it
+        #[clap(long)]
+        simulate: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum OfflineCommand {
+    /// Print an unsigned transaction that uses a durable nonce
+    Build {
+        /// Nonce account whose nonce replaces the recent blockhash
+        #[clap(long)]
+        nonce: Pubkey,
+        /// Account paying the transaction fees
+        #[clap(long)]
+        fee_payer: Pubkey,
+        /// Output encoding: base64 or base58
+        #[clap(long, default_value = "base64")]
+        encoding: TransactionEncoding,
+        #[clap(subcommand)]
+        transaction: OfflineTransaction,
+    },
+    /// Add the keypair's signature to an encoded transaction, `-` reads it
+    /// from stdin
+    Sign {
+        transaction: String,
+        #[clap(long, default_value = "base64")]
+        encoding: TransactionEncoding,
+        /// Another keypair to sign with, in any form `--keypair` accepts.
+        /// Keypairs the transaction does not ask for are skipped
+        #[clap(long = "signer")]
+        signers: Vec<String>,
+    },
+    /// Print who has signed an encoded transaction so far, including the
+    /// members of the multisigs it uses, `-` reads it from stdin
+    Status {
+        transaction: String,
+        #[clap(long, default_value = "base64")]
+        encoding: TransactionEncoding,
+    },
+    /// Send a fully signed encoded transaction, `-` reads it from stdin
+    Broadcast {
+        transaction: String,
+        #[clap(long, default_value = "base64")]
+        encoding: TransactionEncoding,
+        /// Simulate the transaction and print its logs and account changes
+        /// instead of sending it
+        #[clap(long)]
+        simulate: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum OfflineTransaction {
+    /// Transfer SOL
+    Transfer {
+        from: Pubkey,
+        to: Pubkey,
+        amount: f64,
+    },
+    /// Mint tokens of an SPL token mint
+    MintTokens {
+        mint: Pubkey,
+        destination: Pubkey,
+        authority: Pubkey,
+        /// Amount in base units
+        amount: u64,
+        /// Multisig member signing for `authority`, repeat for each member
+        #[clap(long = "signer")]
+        signers: Vec<Pubkey>,
+    },
+    /// Replace or remove an authority of a mint or token account
+    SetAuthority {
+        /// Mint or token account
+        account: Pubkey,
+        /// Current authority
+        authority: Pubkey,
+        /// mint-tokens, freeze-account, account-owner, close-account or one
+        /// of the Token-2022 authorities
+        #[clap(long, value_parser = multisig::parse_authority_type)]
+        authority_type: AuthorityType,
+        /// New authority, the authority is removed when omitted
+        #[clap(long)]
+        new_authority: Option<Pubkey>,
+        /// Multisig member signing for `authority`, repeat for each member
+        #[clap(long = "signer")]
+        signers: Vec<Pubkey>,
+    },
+    /// Create a student intro
+    IntroCreate {
+        author: Pubkey,
+        #[clap(long)]
+        name: String,
+        #[clap(long)]
+        message: String,
+    },
+    /// Update a student intro
+    IntroUpdate {
+        author: Pubkey,
+        #[clap(long)]
+        name: String,
+        #[clap(long)]
+        message: String,
+    },
+}
+
+impl OfflineTransaction {
+    fn instructions(
+        &self,
+        client: &RpcClient,
+        profile: &Profile,
+    ) -> anyhow::Result<Vec<Instruction>> {
+        let instruction = match self {
+            OfflineTransaction::Transfer {
+                from,
+                to,
+                amount,
+            } => {
+                system_instruction::transfer(from, to, sol_to_lamports(*amount))
+            }
+            OfflineTransaction::MintTokens {
+                mint,
+                destination,
+                authority,
+                amount,
+                signers,
+            } => multisig::mint_to_instruction(
+                client,
+                mint,
+                destination,
+                authority,
+                signers,
+                *amount,
+            )?,
+            OfflineTransaction::SetAuthority {
+                account,
+                authority,
+                authority_type,
+                new_authority,
+                signers,
+            } => multisig::set_authority_instruction(
+                client,
+                account,
+                new_authority.as_ref(),
+                authority_type.clone(),
+                authority,
+                signers,
+            )?,
+            OfflineTransaction::IntroCreate {
+                author,
+                name,
+                message,
+            } => intro::create_student_intro_instruction(
+                &intro_program_id(profile)?,
+                author,
+                name,
+                message,
+            ),
+            OfflineTransaction::IntroUpdate {
+                author,
+                name,
+                message,
+            } => intro::update_student_intro_instruction(
+                &intro_program_id(profile)?,
+                author,
+                name,
+                message,
+            ),
+        };
+        Ok(vec![instruction])
+    }
+}
+
+#[derive(Args)]
+struct QueryArgs {
+    /// Only include intros whose name starts with this prefix
+    #[clap(long)]
+    name_prefix: Option<String>,
+
+    /// Only include intros with exactly this name
+    #[clap(long)]
+    name: Option<String>,
+
+    /// Only include intros with this variant byte
+    #[clap(long)]
+    variant: Option<u8>,
+}
+
+impl QueryArgs {
+    fn query(&self) -> IntroQuery {
+        let mut query = IntroQuery::new();
+        if let Some(prefix) = &self.name_prefix {
+            query = query.name_prefix(prefix);
+        }
+        if let Some(name) = &self.name {
+            query = query.name(name);
+        }
+        if let Some(variant) = self.variant {
+            query = query.variant(variant);
+        }
+        query
+    }
+}
+
+fn load_keypair(
+    source: Option<&str>,
+    derivation_path: Option<&str>,
+) -> anyhow::Result<Keypair> {
+    let derivation_path = derivation_path
+        .map(DerivationPath::from_absolute_path_str)
+        .transpose()?;
+
+    let keypair = match source {
+        Some(source) => keypair::load_keypair(source, derivation_path)?,
+        None => keypair::load_default_keypair()?,
+    };
+    Ok(keypair)
+}
+
+/// Reads an encoded transaction from the argument, or stdin for `-`.
+fn read_transaction(
+    transaction: &str,
+    encoding: TransactionEncoding,
+) -> anyhow::Result<Transaction> {
+    let encoded = if transaction == "-" {
+        io::read_to_string(io::stdin())?
+    } else {
+        transaction.to_string()
+    };
+    Ok(offline::decode_transaction(&encoded, encoding)?)
+}
+
+fn join_pubkeys(pubkeys: &[Pubkey]) -> String {
+    pubkeys.iter().map(Pubkey::to_string).collect::<Vec<_>>().join(", ")
+}
+
+fn print_transaction(cluster: &Cluster, sig: &Signature) {
+    println!(
+        "You can view your transaction on the Solana Explorer at:\n{}",
+        cluster.explorer_transaction_url(sig)
+    );
+}
+
+/// Prepends compute budget instructions for a dry run. If the budget
+/// simulation already fails, the instructions are left as they are so the
+/// dry run shows why.
+fn budget_for_simulation(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+    budget: &ComputeBudgetConfig,
+) -> anyhow::Result<Vec<Instruction>> {
+    match estimate_versioned_compute_budget(
+        client,
+        instructions,
+        payer,
+        lookup_tables,
+        budget,
+    ) {
+        Ok(budget) => {
+            Ok([budget.instructions(), instructions.to_vec()].concat())
+        }
+        Err(e) if e.get_transaction_error().is_some() => {
+            Ok(instructions.to_vec())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Prints a simulation, along with the student intro events it logs when
+/// `program_id` is the intro program it calls.
+fn print_simulation(simulation: &Simulation, program_id: Option<&Pubkey>) {
+    println!("Logs:");
+    for log in &simulation.logs {
+        println!("  {log}");
+    }
+    let events = program_id.map_or_else(Vec::new, |program_id| {
+        parse_intro_events(&simulation.logs, program_id)
+    });
+    if !events.is_empty() {
+        println!("Events:");
+        for event in &events {
+            println!("  {event:?}");
+        }
+    }
+    match simulation.units_consumed {
+        Some(units) => println!("Compute units consumed: {units}"),
+        None => println!("Compute units consumed: unknown"),
+    }
+    if let Some(err) = &simulation.err {
+        println!("Transaction would fail: {err}");
+        return;
+    }
+
+    println!("Account changes:");
+    for change in &simulation.changes {
+        if !change.is_changed() {
+            println!("  {}: unchanged", change.pubkey);
+            continue;
+        }
+
+        println!("  {}:", change.pubkey);
+        let (before, after) = (change.before.as_ref(), change.after.as_ref());
+        match (before, after) {
+            (None, Some(_)) => println!("    created"),
+            (Some(_), None) => println!("    closed"),
+            _ => {}
+        }
+
+        let lamports =
+            |account: Option<&Account>| account.map_or(0, |a| a.lamports);
+        println!(
+            "    lamports: {} -> {} ({:+})",
+            lamports(before),
+            lamports(after),
+            change.lamports_delta()
+        );
+        if let (Some(before), Some(after)) = (before, after) {
+            if before.owner != after.owner {
+                println!("    owner: {} -> {}", before.owner, after.owner);
+            }
+        }
+        let data_len =
+            |account: Option<&Account>| account.map_or(0, |a| a.data.len());
+        println!(
+            "    data: {} -> {} bytes, {} bytes changed",
+            data_len(before),
+            data_len(after),
+            change.data_bytes_changed()
+        );
+    }
+}
+
+fn print_outcome(
+    cluster: &Cluster,
+    outcome: &SubmitOutcome,
+) -> anyhow::Result<()> {
+    match outcome {
+        SubmitOutcome::Confirmed(sig) => {
+            print_transaction(cluster, sig);
+            Ok(())
+        }
+        SubmitOutcome::Failed {
+            signature,
+            err,
+        } => anyhow::bail!("Transaction {signature} failed: {err}"),
+        SubmitOutcome::Expired(sig) => {
+            anyhow::bail!("Transaction {sig} expired before it landed")
+        }
+    }
+}
+
+fn print_inspection(inspection: &Inspection) {
+    let mut rows = vec![
+        ("pubkey", inspection.pubkey.to_string()),
+        ("owner", inspection.owner.to_string()),
+        (
+            "lamports",
+            format!(
+                "{} ({} SOL)",
+                inspection.lamports,
+                lamports_to_sol(inspection.lamports)
+            ),
+        ),
+        (
+            "rent exempt minimum",
+            format!(
+                "{} ({} SOL)",
+                inspection.rent_exempt_minimum,
+                lamports_to_sol(inspection.rent_exempt_minimum)
+            ),
+        ),
+        ("data length", inspection.data_len.to_string()),
+        ("executable", inspection.executable.to_string()),
+        ("kind", inspection.data.kind().to_string()),
+    ];
+    rows.extend(inspection.data.fields().into_iter().map(|(name, value)| {
+        let value = match value {
+            serde_json::Value::String(value) => value,
+            serde_json::Value::Null => "none".to_string(),
+            value => value.to_string(),
+        };
+        (name, value)
+    }));
+
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, value) in rows {
+        println!("{name:<width$}  {value}");
+    }
+}
+
+fn print_multisig_status(status: &MultisigStatus) {
+    println!(
+        "\nMultisig {}: {} of {} required signatures{}",
+        status.multisig,
+        status.signed.len(),
+        status.m,
+        if status.is_satisfied() {
+            ", ready"
+        } else {
+            ""
+        }
+    );
+    for (members, state) in [
+        (&status.signed, "signed"),
+        (&status.pending, "missing"),
+        (&status.absent, "not asked to sign"),
+    ] {
+        for member in members {
+            println!("  {member}  {state}");
+        }
+    }
+}
+
+fn print_token_metadata(metadata: &TokenMetadata) {
+    let update_authority = metadata
+        .update_authority
+        .map_or_else(|| "none".to_string(), |authority| authority.to_string());
+    let mut rows = vec![
+        ("name", metadata.name.as_str()),
+        ("symbol", metadata.symbol.as_str()),
+        ("uri", metadata.uri.as_str()),
+        ("update authority", update_authority.as_str()),
+    ];
+    rows.extend(
+        metadata
+            .additional_metadata
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str())),
+    );
+
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, value) in rows {
+        println!("{name:<width$}  {value}");
+    }
+}
+
+fn print_call(call: &IntroCall, json: bool) {
+    let (instruction, args) = match &call.instruction {
+        Ok(IntroInstruction::CreateAccount {
+            name,
+            message,
+        }) => (
+            "CreateAccount",
+            serde_json::json!({ "name": name, "message": message }),
+        ),
+        Ok(IntroInstruction::UpdateAccount {
+            name,
+            message,
+        }) => (
+            "UpdateAccount",
+            serde_json::json!({ "name": name, "message": message }),
+        ),
+        Ok(IntroInstruction::Reply {
+            reply,
+        }) => ("Reply", serde_json::json!({ "reply": reply })),
+        Ok(IntroInstruction::InitializeMint) => {
+            ("InitializeMint", serde_json::json!({}))
+        }
+        Ok(IntroInstruction::InitializeMetadataMint {
+            name,
+            symbol,
+            uri,
+        }) => (
+            "InitializeMetadataMint",
+            serde_json::json!({ "name": name, "symbol": symbol, "uri": uri }),
+        ),
+        Ok(IntroInstruction::UpdateMintMetadata {
+            field,
+            value,
+        }) => (
+            "UpdateMintMetadata",
+            serde_json::json!({ "field": field, "value": value }),
+        ),
+        Err(e) => ("Unknown", serde_json::json!({ "error": e })),
+    };
+    let caller = call.caller.map(|caller| caller.to_string());
+    let invoked_by = call.invoked_by.map(|program| program.to_string());
+
+    if json {
+        let line = serde_json::json!({
+            "signature": call.signature.to_string(),
+            "slot": call.slot,
+            "block_time": call.block_time,
+            "caller": caller,
+            "invoked_by": invoked_by,
+            "instruction": instruction,
+            "args": args,
+            "success": call.succeeded(),
+            "error": call.error,
+        });
+        println!("{line}");
+        return;
+    }
+
+    let status = match &call.error {
+        None => "ok".to_string(),
+        Some(e) => format!("failed: {e}"),
+    };
+    println!("{} (slot {}) {status}", call.signature, call.slot);
+    println!(
+        "  {instruction} {args} by {}",
+        caller.as_deref().unwrap_or("unknown")
+    );
+    if let Some(program) = invoked_by {
+        println!("  invoked by {program}");
+    }
+}
+
+fn print_update(update: &IntroUpdate, json: bool) {
+    let kind = match update.kind {
+        ChangeKind::Created => "created",
+        ChangeKind::Updated => "updated",
+        ChangeKind::Closed => "closed",
+    };
+
+    if json {
+        let (name, message, error) = match &update.intro {
+            Ok(intro) => (Some(&intro.name), Some(&intro.message), None),
+            Err(e) => (None, None, Some(e)),
+        };
+        let line = serde_json::json!({
+            "kind": kind,
+            "pubkey": update.pubkey.to_string(),
+            "slot": update.slot,
+            "lamports": update.lamports,
+            "data_len": update.data_len,
+            "name": name,
+            "message": message,
+            "error": error,
+        });
+        println!("{line}");
+        return;
+    }
+
+    match &update.intro {
+        Ok(intro) => println!(
+            "[{kind}] {}: {} - {}",
+            update.pubkey, intro.name, intro.message
+        ),
+        Err(e) => println!("[{kind}] {}: {e}", update.pubkey),
+    }
+}
+
+fn intro_program_id(profile: &Profile) -> anyhow::Result<Pubkey> {
+    profile.program_id(STUDENT_INTRO_PROGRAM).ok_or_else(|| {
+        anyhow::anyhow!(
+            "no student intro program id, configure \
+             `{STUDENT_INTRO_PROGRAM}` under the profile's program_ids or \
+             pass --program-id"
+        )
+    })
+}
+
+fn mint_program_id(profile: &Profile) -> anyhow::Result<Pubkey> {
+    profile.program_id(STUDENT_INTRO_MINT_PROGRAM).ok_or_else(|| {
+        anyhow::anyhow!(
+            "no mint-enabled student intro program id, configure \
+             `{STUDENT_INTRO_MINT_PROGRAM}` under the profile's program_ids \
+             or pass --mint-program-id"
+        )
+    })
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let profile = cli.profile()?;
+    let client =
+        RpcClient::new_with_commitment(&profile.rpc_url, profile.commitment);
+    let budget = cli.compute_budget();
+    let submit_config = cli.submit_config();
+
+    match cli.command {
+        Command::Balance {
+            pubkey,
+        } => {
+            let lam = client.get_balance(&pubkey)?;
+            println!("Balance: {}", lamports_to_sol(lam));
+        }
+        Command::Account {
+            pubkey,
+        } => {
+            let account = client.get_account(&pubkey)?;
+            println!("Is account executable? {}", account.executable);
+        }
+        Command::Inspect {
+            pubkey,
+            json,
+        } => {
+            let inspection = inspect(
+                &client,
+                &pubkey,
+                profile.program_id(STUDENT_INTRO_PROGRAM).as_ref(),
+            )?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&inspection.to_json())?
+                );
+            } else {
+                print_inspection(&inspection);
+            }
+        }
+        Command::Airdrop {
+            amount,
+            to,
+        } => {
+            let pubkey = match to {
+                Some(pubkey) => pubkey,
+                None => load_keypair(
+                    profile.keypair.as_deref(),
+                    cli.derivation_path.as_deref(),
+                )?
+                .pubkey(),
+            };
+            if !profile.cluster.supports_airdrops() {
+                anyhow::bail!("profile `{}` has no faucet", profile.name);
+            }
+            let lam = ensure_funded(&client, &pubkey, sol_to_lamports(amount))?;
+            println!("Balance: {}", lamports_to_sol(lam));
+        }
+        Command::Transfer {
+            to,
+            amount,
+            simulate: true,
+        } => {
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            let instructions = budget_for_simulation(
+                &client,
+                &[system_instruction::transfer(
+                    &payer.pubkey(),
+                    &to,
+                    sol_to_lamports(amount),
+                )],
+                &payer.pubkey(),
+                &[],
+                &budget,
+            )?;
+            print_simulation(
+                &simulate(&client, &instructions, &payer.pubkey(), &[&payer])?,
+                None,
+            );
+        }
+        Command::Transfer {
+            to,
+            amount,
+            simulate: false,
+        } => {
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            let outcome = transfer::send_sol(
+                &client,
+                sol_to_lamports(amount),
+                &to,
+                &payer,
+                &budget,
+                &submit_config,
+            )?;
+            print_outcome(&profile.cluster, &outcome)?;
+        }
+        Command::Payout {
+            manifest,
+            journal,
+            dry_run,
+            simulate: simulate_batches,
+        } => {
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            let payouts = payout::read_manifest(File::open(&manifest)?)?;
+            let batches = payout::pack_batches(&payer.pubkey(), &payouts);
+            let journal_path = journal.unwrap_or_else(|| {
+                let mut path = manifest.into_os_string();
+                path.push(".journal");
+                path.into()
+            });
+            let mut journal = Journal::open(&journal_path)?;
+
+            if dry_run {
+                let estimate = payout::estimate(
+                    &client,
+                    &payer.pubkey(),
+                    &batches,
+                    &journal,
+                )?;
+                println!(
+                    "{} transfers in {} transactions left to send",
+                    estimate.transfers, estimate.batches
+                );
+                if estimate.landed > 0 {
+                    println!(
+                        "{} transactions from an earlier run already landed",
+                        estimate.landed
+                    );
+                }
+                println!(
+                    "Transfers: {} SOL",
+                    lamports_to_sol(estimate.lamports)
+                );
+                println!("Fees: {} SOL", lamports_to_sol(estimate.fees));
+                println!(
+                    "Total: {} SOL",
+                    lamports_to_sol(estimate.lamports + estimate.fees)
+                );
+                return Ok(());
+            }
+
+            if simulate_batches {
+                for batch in &batches {
+                    if journal.is_confirmed(batch) {
+                        continue;
+                    }
+                    println!(
+                        "Batch {}: {} transfers",
+                        batch.index,
+                        batch.payouts.len()
+                    );
+                    print_simulation(
+                        &simulate(
+                            &client,
+                            &batch.instructions(&payer.pubkey()),
+                            &payer.pubkey(),
+                            &[&payer],
+                        )?,
+                        None,
+                    );
+                }
+                return Ok(());
+            }
+
+            payout::run_payouts(
+                &client,
+                &payer,
+                &batches,
+                &mut journal,
+                |batch, outcome| {
+                    let count = batch.payouts.len();
+                    match outcome {
+                        BatchOutcome::AlreadyPaid => println!(
+                            "Batch {}: {count} transfers already paid",
+                            batch.index
+                        ),
+                        BatchOutcome::Recovered(sig)
+                        | BatchOutcome::Sent(sig) => {
+                            println!(
+                                "Batch {}: {count} transfers",
+                                batch.index
+                            );
+                            print_transaction(&profile.cluster, &sig);
+                        }
+                    }
+                },
+            )?;
+        }
+        Command::Intro(IntroCommand::Create {
+            name,
+            message,
+            simulate: true,
+        }) => {
+            let program_id = intro_program_id(&profile)?;
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            let instructions = budget_for_simulation(
+                &client,
+                &[intro::create_student_intro_instruction(
+                    &program_id,
+                    &payer.pubkey(),
+                    &name,
+                    &message,
+                )],
+                &payer.pubkey(),
+                &[],
+                &budget,
+            )?;
+            print_simulation(
+                &simulate(&client, &instructions, &payer.pubkey(), &[&payer])?,
+                Some(&program_id),
+            );
+        }
+        Command::Intro(IntroCommand::Create {
+            name,
+            message,
+            simulate: false,
+        }) => {
+            let program_id = intro_program_id(&profile)?;
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            let sig = intro::create_student_intro(
+                &client,
+                &program_id,
+                &payer,
+                &name,
+                &message,
+                &budget,
+            )
+            .map_err(|e| {
+                let programs = budgeted_program_ids(&[
+                    intro::create_student_intro_instruction(
+                        &program_id,
+                        &payer.pubkey(),
+                        &name,
+                        &message,
+                    ),
+                ]);
+                anyhow::anyhow!(describe_client_error(
+                    &e,
+                    &programs,
+                    &program_id
+                ))
+            })?;
+            print_transaction(&profile.cluster, &sig);
+        }
+        Command::Intro(IntroCommand::CreateMinted {
+            name,
+            message,
+            lookup_table,
+            simulate: simulate_transaction,
+        }) => {
+            let mint_program_id = mint_program_id(&profile)?;
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            let lookup_table = match lookup_table {
+                Some(table) => Some(get_address_lookup_table(&client, &table)?),
+                None => None,
+            };
+            if simulate_transaction {
+                let lookup_tables = lookup_table.as_slice();
+                let instructions = budget_for_simulation(
+                    &client,
+                    &mint_intro::create_student_intro_with_mint_instructions(
+                        &mint_program_id,
+                        &payer.pubkey(),
+                        &name,
+                        &message,
+                    ),
+                    &payer.pubkey(),
+                    lookup_tables,
+                    &budget,
+                )?;
+                let simulation = simulate_versioned(
+                    &client,
+                    &instructions,
+                    &payer.pubkey(),
+                    &[&payer],
+                    lookup_tables,
+                )?;
+                print_simulation(&simulation, Some(&mint_program_id));
+                return Ok(());
+            }
+            let sig = mint_intro::create_student_intro_with_mint(
+                &client,
+                &mint_program_id,
+                &payer,
+                &name,
+                &message,
+                lookup_table.as_ref(),
+                &budget,
+            )
+            .map_err(|e| {
+                let programs = budgeted_program_ids(
+                    &mint_intro::create_student_intro_with_mint_instructions(
+                        &mint_program_id,
+                        &payer.pubkey(),
+                        &name,
+                        &message,
+                    ),
+                );
+                anyhow::anyhow!(describe_client_error(
+                    &e,
+                    &programs,
+                    &mint_program_id
+                ))
+            })?;
+            print_transaction(&profile.cluster, &sig);
+        }
+        Command::Intro(IntroCommand::InitMetadataMint {
+            name,
+            symbol,
+            uri,
+        }) => {
+            let mint_program_id = mint_program_id(&profile)?;
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            let sig = mint_intro::initialize_metadata_mint(
+                &client,
+                &mint_program_id,
+                &payer,
+                &name,
+                &symbol,
+                &uri,
+                &budget,
+            )
+            .map_err(|e| {
+                let programs = budgeted_program_ids(&[
+                    mint_intro::initialize_metadata_mint_instruction(
+                        &mint_program_id,
+                        &payer.pubkey(),
+                        &name,
+                        &symbol,
+                        &uri,
+                    ),
+                ]);
+                anyhow::anyhow!(describe_client_error(
+                    &e,
+                    &programs,
+                    &mint_program_id
+                ))
+            })?;
+            println!(
+                "Reward mint: {}",
+                mint_intro::metadata_mint_address(&mint_program_id)
+            );
+            print_transaction(&profile.cluster, &sig);
+        }
+        Command::Intro(IntroCommand::SetMintMetadata {
+            field,
+            value,
+        }) => {
+            let mint_program_id = mint_program_id(&profile)?;
+            let authority = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            let sig = mint_intro::update_mint_metadata(
+                &client,
+                &mint_program_id,
+                &authority,
+                &field,
+                &value,
+                &budget,
+            )
+            .map_err(|e| {
+                let programs = budgeted_program_ids(&[
+                    mint_intro::update_mint_metadata_instruction(
+                        &mint_program_id,
+                        &authority.pubkey(),
+                        &field,
+                        &value,
+                    ),
+                ]);
+                anyhow::anyhow!(describe_client_error(
+                    &e,
+                    &programs,
+                    &mint_program_id
+                ))
+            })?;
+            print_transaction(&profile.cluster, &sig);
+        }
+        Command::Intro(IntroCommand::CreateLookupTable {
+            simulate: simulate_transaction,
+        }) => {
+            let mint_program_id = mint_program_id(&profile)?;
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            if simulate_transaction {
+                let (table, instructions) = create_lookup_table_instructions(
+                    &client,
+                    &payer.pubkey(),
+                    &payer.pubkey(),
+                    &mint_intro::lookup_table_addresses(&mint_program_id),
+                )?;
+                let instructions = budget_for_simulation(
+                    &client,
+                    &instructions,
+                    &payer.pubkey(),
+                    &[],
+                    &budget,
+                )?;
+                println!("Lookup table: {table}");
+                print_simulation(
+                    &simulate(
+                        &client,
+                        &instructions,
+                        &payer.pubkey(),
+                        &[&payer],
+                    )?,
+                    None,
+                );
+                return Ok(());
+            }
+            let table = create_address_lookup_table(
+                &client,
+                &payer,
+                &payer,
+                &mint_intro::lookup_table_addresses(&mint_program_id),
+                &budget,
+            )?;
+            println!("Lookup table: {table}");
+        }
+        Command::Intro(IntroCommand::List {
+            query,
+        }) => {
+            let program_id = intro_program_id(&profile)?;
+            for (_pubkey, data) in
+                intro::get_student_intros(&client, &program_id, &query.query())?
+            {
+                println!("Name: {} - Message: {}", data.name, data.message);
+            }
+        }
+        Command::Intro(IntroCommand::Page {
+            page,
+            per_page,
+            after,
+            concurrency,
+            query,
+        }) => {
+            let program_id = intro_program_id(&profile)?;
+            let client = NonblockingRpcClient::new_with_commitment(
+                profile.rpc_url.clone(),
+                profile.commitment,
+            );
+            let mut paginator = IntroPaginator::new(
+                &client,
+                program_id,
+                query.query(),
+                per_page,
+            )
+            .with_fetch_config(FetchConfig {
+                concurrency,
+                ..FetchConfig::default()
+            });
+
+            let runtime = tokio::runtime::Runtime::new()?;
+            let page = runtime.block_on(async {
+                match after {
+                    Some(cursor) => paginator.page_after(&cursor).await,
+                    None => paginator.page(page).await,
+                }
+            })?;
+
+            for (pubkey, data) in &page.items {
+                println!("{pubkey}: {} - {}", data.name, data.message);
+            }
+            println!(
+                "Page {}/{} ({} intros)",
+                page.page, page.total_pages, page.total_items
+            );
+            if let Some(cursor) = page.next_cursor {
+                println!("Next page: --after {cursor}");
+            }
+        }
+        Command::Intro(IntroCommand::Decode) => {
+            let program_id = intro_program_id(&profile)?;
+            let accounts = client.get_program_accounts(&program_id)?;
+            let (decoded, failures) = decode_accounts(accounts);
+
+            for (pubkey, account) in &decoded {
+                match account {
+                    IntroAccount::StudentInfo(info) => {
+                        println!(
+                            "{pubkey} StudentInfo: {} - {}",
+                            info.name, info.msg
+                        )
+                    }
+                    IntroAccount::ReplyCounter(counter) => {
+                        println!("{pubkey} ReplyCounter: {}", counter.counter)
+                    }
+                    IntroAccount::Reply(reply) => println!(
+                        "{pubkey} Reply to {}: {}",
+                        reply.studentinfo, reply.reply
+                    ),
+                    IntroAccount::Unknown => println!("{pubkey} Unknown"),
+                }
+            }
+            for failure in &failures {
+                eprintln!(
+                    "Failed to decode {}: {}",
+                    failure.pubkey, failure.reason
+                );
+            }
+            println!(
+                "Decoded {} accounts, {} failed",
+                decoded.len(),
+                failures.len()
+            );
+        }
+        Command::Intro(IntroCommand::History {
+            limit,
+            before,
+            json,
+        }) => {
+            let program_id = intro_program_id(&profile)?;
+            let page = history::get_intro_history(
+                &client,
+                &program_id,
+                before,
+                limit,
+            )?;
+            for call in &page.calls {
+                print_call(call, json);
+            }
+            if let Some(next) = page.next {
+                eprintln!("Next page: --before {next}");
+            }
+        }
+        Command::Intro(IntroCommand::Export {
+            format,
+            output,
+            concurrency,
+            query,
+        }) => {
+            let program_id = intro_program_id(&profile)?;
+            let client = NonblockingRpcClient::new_with_commitment(
+                profile.rpc_url.clone(),
+                profile.commitment,
+            );
+            let out: Box<dyn Write> = match &output {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(io::stdout().lock()),
+            };
+            let fetch_config = FetchConfig {
+                concurrency,
+                ..FetchConfig::default()
+            };
+
+            let runtime = tokio::runtime::Runtime::new()?;
+            let summary = runtime.block_on(export_intros(
+                &client,
+                &program_id,
+                &query.query(),
+                format,
+                out,
+                fetch_config,
+            ))?;
+            for failure in &summary.failures {
+                eprintln!("Skipped {}: {}", failure.pubkey, failure.reason);
+            }
+            if let Some(path) = output {
+                println!(
+                    "Exported {} intros to {}",
+                    summary.exported,
+                    path.display()
+                );
+            }
+        }
+        Command::Nonce(NonceCommand::Create {
+            authority,
+            simulate: simulate_transaction,
+        }) => {
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            let nonce_account = Keypair::new();
+            if simulate_transaction {
+                let instructions = budget_for_simulation(
+                    &client,
+                    &offline::create_nonce_account_instructions(
+                        &client,
+                        &payer.pubkey(),
+                        &nonce_account.pubkey(),
+                        &authority.unwrap_or_else(|| payer.pubkey()),
+                    )?,
+                    &payer.pubkey(),
+                    &[],
+                    &budget,
+                )?;
+                print_simulation(
+                    &simulate(
+                        &client,
+                        &instructions,
+                        &payer.pubkey(),
+                        &[&payer, &nonce_account],
+                    )?,
+                    None,
+                );
+                return Ok(());
+            }
+            let sig = offline::create_nonce_account(
+                &client,
+                &payer,
+                &nonce_account,
+                &authority.unwrap_or_else(|| payer.pubkey()),
+                &budget,
+            )?;
+            println!("Nonce account: {}", nonce_account.pubkey());
+            print_transaction(&profile.cluster, &sig);
+        }
+        Command::Nonce(NonceCommand::Show {
+            nonce_account,
+        }) => {
+            let nonce = offline::get_nonce(&client, &nonce_account)?;
+            println!("Nonce: {}", nonce.blockhash());
+            println!("Authority: {}", nonce.authority);
+        }
+        Command::Nonce(NonceCommand::Advance {
+            nonce_account,
+            simulate: simulate_transaction,
+        }) => {
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            if simulate_transaction {
+                let instructions = budget_for_simulation(
+                    &client,
+                    &[system_instruction::advance_nonce_account(
+                        &nonce_account,
+                        &payer.pubkey(),
+                    )],
+                    &payer.pubkey(),
+                    &[],
+                    &budget,
+                )?;
+                print_simulation(
+                    &simulate(
+                        &client,
+                        &instructions,
+                        &payer.pubkey(),
+                        &[&payer],
+                    )?,
+                    None,
+                );
+                return Ok(());
+            }
+            let sig = offline::advance_nonce(
+                &client,
+                &payer,
+                &nonce_account,
+                &payer,
+                &budget,
+            )?;
+            print_transaction(&profile.cluster, &sig);
+        }
+        Command::Nonce(NonceCommand::Withdraw {
+            nonce_account,
+            to,
+            amount,
+            simulate: simulate_transaction,
+        }) => {
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            if simulate_transaction {
+                let instructions = budget_for_simulation(
+                    &client,
+                    &[system_instruction::withdraw_nonce_account(
+                        &nonce_account,
+                        &payer.pubkey(),
+                        &to,
+                        sol_to_lamports(amount),
+                    )],
+                    &payer.pubkey(),
+                    &[],
+                    &budget,
+                )?;
+                print_simulation(
+                    &simulate(
+                        &client,
+                        &instructions,
+                        &payer.pubkey(),
+                        &[&payer],
+                    )?,
+                    None,
+                );
+                return Ok(());
+            }
+            let sig = offline::withdraw_from_nonce(
+                &client,
+                &payer,
+                &nonce_account,
+                &payer,
+                &to,
+                sol_to_lamports(amount),
+                &budget,
+            )?;
+            print_transaction(&profile.cluster, &sig);
+        }
+        Command::Offline(OfflineCommand::Build {
+            nonce,
+            fee_payer,
+            encoding,
+            transaction,
+        }) => {
+            let transaction = offline::build_nonce_transaction(
+                &client,
+                &transaction.instructions(&client, &profile)?,
+                &fee_payer,
+                &nonce,
+                &budget,
+            )?;
+            println!("{}", offline::encode_transaction(&transaction, encoding));
+            eprintln!(
+                "Signers: {}",
+                join_pubkeys(&offline::missing_signers(&transaction))
+            );
+        }
+        Command::Offline(OfflineCommand::Sign {
+            transaction,
+            encoding,
+            signers,
+        }) => {
+            let mut keypairs = signers
+                .iter()
+                .map(|source| keypair::load_keypair(source, None))
+                .collect::<Result<Vec<_>, _>>()?;
+            // The profile keypair signs too when the transaction asks for
+            // it, but only has to exist without `--signer`.
+            match load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            ) {
+                Ok(keypair) => keypairs.push(keypair),
+                Err(e) if keypairs.is_empty() => return Err(e),
+                Err(_) => {}
+            }
+            let mut transaction = read_transaction(&transaction, encoding)?;
+            let signed =
+                offline::sign_transaction(&mut transaction, &keypairs)?;
+            println!("{}", offline::encode_transaction(&transaction, encoding));
+
+            eprintln!("Signed by: {}", join_pubkeys(&signed));
+
+            let missing = offline::missing_signers(&transaction);
+            if missing.is_empty() {
+                eprintln!("Fully signed, ready to broadcast");
+            } else {
+                eprintln!("Still missing: {}", join_pubkeys(&missing));
+            }
+        }
+        Command::Offline(OfflineCommand::Status {
+            transaction,
+            encoding,
+        }) => {
+            let transaction = read_transaction(&transaction, encoding)?;
+            let missing = offline::missing_signers(&transaction);
+            let signers =
+                usize::from(transaction.message.header.num_required_signatures);
+            for signer in &transaction.message.account_keys[..signers] {
+                let state = if missing.contains(signer) {
+                    "missing"
+                } else {
+                    "signed"
+                };
+                println!("{signer}  {state}");
+            }
+            for status in multisig::multisig_status(&client, &transaction)? {
+                print_multisig_status(&status);
+            }
+        }
+        Command::Offline(OfflineCommand::Broadcast {
+            transaction,
+            encoding,
+            simulate: simulate_transaction,
+        }) => {
+            let transaction = read_transaction(&transaction, encoding)?;
+            if simulate_transaction {
+                print_simulation(
+                    &simulate_signed_transaction(
+                        &client,
+                        &transaction.into(),
+                        &[],
+                    )?,
+                    None,
+                );
+                return Ok(());
+            }
+            let sig = offline::broadcast_transaction(&client, &transaction)?;
+            print_transaction(&profile.cluster, &sig);
+        }
+        Command::Token {
+            simulate: simulate_transactions,
+            command,
+        } => {
+            let payer = load_keypair(
+                profile.keypair.as_deref(),
+                cli.derivation_path.as_deref(),
+            )?;
+            // Address of the mint, multisig or token account a command
+            // creates, which signs its own creation.
+            let new_account = Keypair::new();
+            let transactions: Vec<Vec<Instruction>> = match command {
+                TokenCommand::CreateMint {
+                    decimals,
+                    mint_authority,
+                    freeze_authority,
+                    token_2022: true,
+                    transfer_fee_bps,
+                    max_fee,
+                    close_authority,
+                    interest_rate,
+                    default_frozen,
+                    non_transferable,
+                    name,
+                    symbol,
+                    uri,
+                } => {
+                    let config = MintConfig {
+                        freeze_authority,
+                        transfer_fee: transfer_fee_bps.map(|basis_points| {
+                            TransferFee {
+                                basis_points,
+                                maximum_fee: max_fee,
+                            }
+                        }),
+                        close_authority,
+                        interest_rate,
+                        default_account_state: default_frozen
+                            .then_some(AccountState::Frozen),
+                        non_transferable,
+                        metadata: name.zip(symbol).map(|(name, symbol)| {
+                            MintMetadata {
+                                name,
+                                symbol,
+                                uri,
+                            }
+                        }),
+                        ..MintConfig::new(
+                            decimals,
+                            mint_authority.unwrap_or_else(|| payer.pubkey()),
+                        )
+                    };
+                    println!("Mint: {}", new_account.pubkey());
+                    vec![token_2022::create_mint_instructions(
+                        &client,
+                        &payer.pubkey(),
+                        &new_account.pubkey(),
+                        &config,
+                    )?]
+                }
+                TokenCommand::CreateMint {
+                    decimals,
+                    mint_authority,
+                    freeze_authority,
+                    token_2022: false,
+                    ..
+                } => {
+                    println!("Mint: {}", new_account.pubkey());
+                    vec![token::create_mint_instructions(
+                        &client,
+                        &payer.pubkey(),
+                        &new_account.pubkey(),
+                        &mint_authority.unwrap_or_else(|| payer.pubkey()),
+                        freeze_authority.as_ref(),
+                        decimals,
+                    )?]
+                }
+                TokenCommand::CreateMultisig {
+                    m,
+                    signers,
+                    token_2022,
+                } => {
+                    let token_program = if token_2022 {
+                        spl_token_2022::id()
+                    } else {
+                        spl_token::id()
+                    };
+                    println!(
+                        "Multisig: {} ({m} of {})",
+                        new_account.pubkey(),
+                        signers.len()
+                    );
+                    vec![multisig::create_multisig_instructions(
+                        &client,
+                        &payer.pubkey(),
+                        &new_account.pubkey(),
+                        &signers,
+                        m,
+                        &token_program,
+                    )?]
+                }
+                TokenCommand::CreateAccount {
+                    mint,
+                    owner,
+                    auxiliary: true,
+                } => {
+                    println!("Token account: {}", new_account.pubkey());
+                    vec![token::create_token_account_instructions(
+                        &client,
+                        &payer.pubkey(),
+                        &new_account.pubkey(),
+                        &mint,
+                        &owner.unwrap_or_else(|| payer.pubkey()),
+                    )?]
+                }
+                TokenCommand::CreateAccount {
+                    mint,
+                    owner,
+                    auxiliary: false,
+                } => {
+                    let owner = owner.unwrap_or_else(|| payer.pubkey());
+                    let account = token::associated_token_address(
+                        &owner,
+                        &mint,
+                        &token::token_program(&client, &mint)?,
+                    );
+                    println!("Token account: {account}");
+                    if client
+                        .get_account_with_commitment(
+                            &account,
+                            client.commitment(),
+                        )?
+                        .value
+                        .is_some()
+                    {
+                        return Ok(());
+                    }
+                    vec![vec![
+                        token::create_associated_token_account_instruction(
+                            &client,
+                            &payer.pubkey(),
+                            &owner,
+                            &mint,
+                        )?,
+                    ]]
+                }
+                TokenCommand::Mint {
+                    mint,
+                    amount,
+                    to_account: Some(account),
+                    ..
+                } => vec![vec![token::mint_to_instruction(
+                    &client,
+                    &mint,
+                    &account,
+                    &payer.pubkey(),
+                    amount,
+                )?]],
+                TokenCommand::Mint {
+                    mint,
+                    amount,
+                    to,
+                    to_account: None,
+                } => vec![token::mint_to_wallet_instructions(
+                    &client,
+                    &payer.pubkey(),
+                    &mint,
+                    &to.unwrap_or_else(|| payer.pubkey()),
+                    &payer.pubkey(),
+                    amount,
+                )?],
+                TokenCommand::Transfer {
+                    mint,
+                    amount,
+                    to,
+                    to_account,
+                    from,
+                } => {
+                    let source = TokenAccountArg {
+                        account: from,
+                    }
+                    .resolve(
+                        &client,
+                        &payer.pubkey(),
+                        &mint,
+                    )?;
+                    match (to_account, to) {
+                        (Some(account), _) => {
+                            vec![vec![token::transfer_checked_instruction(
+                                &client,
+                                &source,
+                                &account,
+                                &payer.pubkey(),
+                                amount,
+                            )?]]
+                        }
+                        (None, Some(wallet)) => {
+                            vec![token::transfer_to_wallet_instructions(
+                                &client,
+                                &payer.pubkey(),
+                                &source,
+                                &wallet,
+                                &payer.pubkey(),
+                                amount,
+                            )?]
+                        }
+                        (None, None) => {
+                            anyhow::bail!("--to or --to-account is required")
+                        }
+                    }
+                }
+                TokenCommand::Burn {
+                    mint,
+                    amount,
+                    account,
+                } => vec![vec![token::burn_checked_instruction(
+                    &client,
+                    &account.resolve(&client, &payer.pubkey(), &mint)?,
+                    &payer.pubkey(),
+                    amount,
+                )?]],
+                TokenCommand::Approve {
+                    mint,
+                    delegate,
+                    amount,
+                    account,
+                } => vec![vec![token::approve_instruction(
+                    &client,
+                    &account.resolve(&client, &payer.pubkey(), &mint)?,
+                    &delegate,
+                    &payer.pubkey(),
+                    amount,
+                )?]],
+                TokenCommand::Revoke {
+                    mint,
+                    account,
+                } => vec![vec![token::revoke_instruction(
+                    &client,
+                    &account.resolve(&client, &payer.pubkey(), &mint)?,
+                    &payer.pubkey(),
+                )?]],
+                TokenCommand::Close {
+                    mint,
+                    destination,
+                    account,
+                } => vec![vec![token::close_account_instruction(
+                    &client,
+                    &account.resolve(&client, &payer.pubkey(), &mint)?,
+                    &destination.unwrap_or_else(|| payer.pubkey()),
+                    &payer.pubkey(),
+                )?]],
+                TokenCommand::SyncNative {
+                    account,
+                } => vec![vec![token::sync_native_instruction(
+                    &client,
+                    &account.resolve(
+                        &client,
+                        &payer.pubkey(),
+                        &spl_token::native_mint::id(),
+                    )?,
+                )?]],
+                TokenCommand::Metadata {
+                    mint,
+                } => {
+                    match token_metadata::get_token_metadata(&client, &mint)? {
+                        Some(metadata) => print_token_metadata(&metadata),
+                        None => println!("{mint} has no token metadata"),
+                    }
+                    return Ok(());
+                }
+                TokenCommand::SetMetadata {
+                    mint,
+                    field,
+                    value,
+                } => vec![token_metadata::set_field_instructions(
+                    &client,
+                    &payer.pubkey(),
+                    &mint,
+                    &payer.pubkey(),
+                    &field,
+                    &value,
+                )?],
+                TokenCommand::HarvestFees {
+                    mint,
+                } => {
+                    let sources = token_2022::accounts_with_withheld_fees(
+                        &client, &mint,
+                    )?;
+                    println!(
+                        "Harvesting the fees of {} token accounts",
+                        sources.len()
+                    );
+                    token_2022::harvest_withheld_fees_instructions(
+                        &mint, &sources,
+                    )?
+                    .into_iter()
+                    .map(|instruction| vec![instruction])
+                    .collect()
+                }
+                TokenCommand::WithdrawFees {
+                    mint,
+                    account,
+                } => {
+                    let destination =
+                        account.resolve(&client, &payer.pubkey(), &mint)?;
+                    let sources = token_2022::accounts_with_withheld_fees(
+                        &client, &mint,
+                    )?;
+                    token_2022::withdraw_withheld_fees_instructions(
+                        &mint,
+                        &destination,
+                        &payer.pubkey(),
+                        &sources,
+                    )?
+                    .into_iter()
+                    .map(|instruction| vec![instruction])
+                    .collect()
+                }
+            };
+
+            let mut signers = vec![&payer];
+            let signs_new_account = transactions.iter().flatten().any(|ix| {
+                ix.accounts.iter().any(|account| {
+                    account.is_signer && account.pubkey == new_account.pubkey()
+                })
+            });
+            if signs_new_account {
+                signers.push(&new_account);
+            }
+
+            for instructions in &transactions {
+                if simulate_transactions {
+                    let instructions = budget_for_simulation(
+                        &client,
+                        instructions,
+                        &payer.pubkey(),
+                        &[],
+                        &budget,
+                    )?;
+                    print_simulation(
+                        &simulate(
+                            &client,
+                            &instructions,
+                            &payer.pubkey(),
+                            &signers,
+                        )?,
+                        None,
+                    );
+                } else {
+                    let outcome = submit_with_compute_budget(
+                        &client,
+                        instructions,
+                        &payer.pubkey(),
+                        &signers,
+                        &budget,
+                        &submit_config,
+                    )?;
+                    print_outcome(&profile.cluster, &outcome)?;
+                }
+            }
+        }
+        Command::Watch {
+            ws_url,
+            existing,
+            json,
+            max_retries,
+            query,
+        } => {
+            let program_id = intro_program_id(&profile)?;
+            let config = WatchConfig {
+                query: query.query(),
+                commitment: profile.commitment,
+                include_existing: existing,
+                max_retries,
+                ..WatchConfig::new(
+                    ws_url.unwrap_or_else(|| profile.ws_url.clone()),
+                )
+            };
+
+            IntroWatcher::new(&client, program_id, config).run(|update| {
+                print_update(&update, json);
+                ControlFlow::Continue(())
+            })?;
+        }
+    }
+
+    Ok(())
+}
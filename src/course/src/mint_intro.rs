@@ -0,0 +1,318 @@
+use crate::{
+    budget::{send_versioned_with_compute_budget, ComputeBudgetConfig},
+    intro::StudentIntro,
+};
+use solana_client::{
+    client_error::Result as ClientResult, rpc_client::RpcClient,
+};
+use solana_sdk::{
+    address_lookup_table_account::AddressLookupTableAccount,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::keypair::Keypair,
+    signer::Signer,
+    system_program,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+
+/// The program's first mint, seeded with `token_mint`. It has no metadata,
+/// new intros are rewarded from the [`metadata_mint_address`] instead.
+pub fn token_mint_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"token_mint"], program_id).0
+}
+
+/// Mint authority of both mints, seeded with `token_auth`.
+pub fn mint_auth_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"token_auth"], program_id).0
+}
+
+/// Token-2022 mint carrying the course token's name, symbol and URI, which
+/// new intros are rewarded from. Seeded with `metadata_mint`.
+pub fn metadata_mint_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"metadata_mint"], program_id).0
+}
+
+pub fn intro_address(program_id: &Pubkey, initializer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[initializer.as_ref()], program_id).0
+}
+
+pub fn reply_counter_address(program_id: &Pubkey, intro: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[intro.as_ref(), b"reply"], program_id).0
+}
+
+/// Accounts shared by every `add_student_intro` call, worth keeping in a
+/// lookup table.
+pub fn lookup_table_addresses(program_id: &Pubkey) -> Vec<Pubkey> {
+    vec![
+        metadata_mint_address(program_id),
+        mint_auth_address(program_id),
+        spl_token_2022::id(),
+        system_program::id(),
+    ]
+}
+
+/// `CreateAccount` instruction of the mint-enabled program, with the ten
+/// accounts `add_student_intro` expects.
+pub fn add_student_intro_instruction(
+    program_id: &Pubkey,
+    initializer: &Pubkey,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let intro = intro_address(program_id, initializer);
+    let reply_counter = reply_counter_address(program_id, &intro);
+    let token_mint = metadata_mint_address(program_id);
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &StudentIntro {
+            variant: 0,
+            name: name.to_string(),
+            message: message.to_string(),
+        },
+        vec![
+            AccountMeta::new(*initializer, true),
+            AccountMeta::new(intro, false),
+            AccountMeta::new(reply_counter, false),
+            AccountMeta::new(token_mint, false),
+            AccountMeta::new_readonly(mint_auth_address(program_id), false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(
+                    initializer,
+                    &token_mint,
+                    &spl_token_2022::id(),
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token_2022::id(), false),
+            AccountMeta::new(reply_counter, false),
+            AccountMeta::new(intro, false),
+        ],
+    )
+}
+
+/// Creates the payer's reward token account if needed and adds their intro
+/// in one transaction.
+///
+/// With a lookup table the transaction is sent as v0, otherwise as a legacy
+/// transaction.
+pub fn create_student_intro_with_mint(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    payer: &Keypair,
+    name: &str,
+    message: &str,
+    lookup_table: Option<&AddressLookupTableAccount>,
+    budget: &ComputeBudgetConfig,
+) -> ClientResult<Signature> {
+    let instructions = create_student_intro_with_mint_instructions(
+        program_id,
+        &payer.pubkey(),
+        name,
+        message,
+    );
+
+    send_versioned_with_compute_budget(
+        client,
+        &instructions,
+        &payer.pubkey(),
+        &[payer],
+        lookup_table.map(std::slice::from_ref).unwrap_or_default(),
+        budget,
+    )
+}
+
+/// Instructions of [`create_student_intro_with_mint`], signed by `payer`.
+pub fn create_student_intro_with_mint_instructions(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    name: &str,
+    message: &str,
+) -> Vec<Instruction> {
+    vec![
+        create_associated_token_account_idempotent(
+            payer,
+            payer,
+            &metadata_mint_address(program_id),
+            &spl_token_2022::id(),
+        ),
+        add_student_intro_instruction(program_id, payer, name, message),
+    ]
+}
+
+/// Creates the metadata mint new intros are rewarded from, with `payer` as
+/// the update authority of its name, symbol and URI.
+pub fn initialize_metadata_mint(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    payer: &Keypair,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+    budget: &ComputeBudgetConfig,
+) -> ClientResult<Signature> {
+    let instruction = initialize_metadata_mint_instruction(
+        program_id,
+        &payer.pubkey(),
+        name,
+        symbol,
+        uri,
+    );
+
+    send_versioned_with_compute_budget(
+        client,
+        &[instruction],
+        &payer.pubkey(),
+        &[payer],
+        &[],
+        budget,
+    )
+}
+
+/// Sets one metadata field of the metadata mint, signed by its update
+/// authority.
+pub fn update_mint_metadata(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    authority: &Keypair,
+    field: &str,
+    value: &str,
+    budget: &ComputeBudgetConfig,
+) -> ClientResult<Signature> {
+    let instruction = update_mint_metadata_instruction(
+        program_id,
+        &authority.pubkey(),
+        field,
+        value,
+    );
+
+    send_versioned_with_compute_budget(
+        client,
+        &[instruction],
+        &authority.pubkey(),
+        &[authority],
+        &[],
+        budget,
+    )
+}
+
+/// `InitializeMetadataMint` instruction of the mint-enabled program, which
+/// creates the metadata mint and makes `payer` the update authority of its
+/// metadata.
+pub fn initialize_metadata_mint_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &(4u8, name, symbol, uri),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(metadata_mint_address(program_id), false),
+            AccountMeta::new_readonly(mint_auth_address(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token_2022::id(), false),
+        ],
+    )
+}
+
+/// `UpdateMintMetadata` instruction of the mint-enabled program, setting
+/// `name`, `symbol`, `uri` or any additional key. Signed by the metadata's
+/// update authority, which pays for the larger account.
+pub fn update_mint_metadata_instruction(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    field: &str,
+    value: &str,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        *program_id,
+        &(5u8, field, value),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(metadata_mint_address(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token_2022::id(), false),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use student_intro_program_mint::instruction::IntroInstruction;
+
+    #[test]
+    fn metadata_instructions_unpack_in_the_program() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+
+        let initialize = initialize_metadata_mint_instruction(
+            &program_id,
+            &payer,
+            "Course",
+            "CRS",
+            "https://example.com",
+        );
+        assert_eq!(
+            IntroInstruction::unpack(&initialize.data),
+            Ok(IntroInstruction::InitializeMetadataMint {
+                name: "Course".to_string(),
+                symbol: "CRS".to_string(),
+                uri: "https://example.com".to_string(),
+            })
+        );
+        assert_eq!(
+            initialize.accounts[1].pubkey,
+            metadata_mint_address(&program_id)
+        );
+
+        let update =
+            update_mint_metadata_instruction(&program_id, &payer, "uri", "u");
+        assert_eq!(
+            IntroInstruction::unpack(&update.data),
+            Ok(IntroInstruction::UpdateMintMetadata {
+                field: "uri".to_string(),
+                value: "u".to_string(),
+            })
+        );
+        assert!(update.accounts[0].is_signer);
+        assert_eq!(
+            update.accounts[1].pubkey,
+            metadata_mint_address(&program_id)
+        );
+    }
+
+    #[test]
+    fn intros_are_rewarded_from_the_metadata_mint() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let mint = metadata_mint_address(&program_id);
+
+        let instructions = create_student_intro_with_mint_instructions(
+            &program_id,
+            &payer,
+            "Ada",
+            "hello",
+        );
+        let ata = get_associated_token_address_with_program_id(
+            &payer,
+            &mint,
+            &spl_token_2022::id(),
+        );
+        assert_eq!(instructions[0].accounts[1].pubkey, ata);
+        let add = &instructions[1];
+        assert_eq!(add.accounts[3].pubkey, mint);
+        assert_eq!(add.accounts[5].pubkey, ata);
+        assert_eq!(add.accounts[7].pubkey, spl_token_2022::id());
+        assert!(lookup_table_addresses(&program_id).contains(&mint));
+    }
+}
@@ -0,0 +1,374 @@
+use crate::token::{
+    is_token_program, send, token_program, SendConfig, TokenError,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{keypair::Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_token_2022::{
+    instruction::{self, AuthorityType, MAX_SIGNERS},
+    state::Multisig,
+};
+
+/// Creates an m-of-n multisig at the address of `multisig`, usable as the
+/// mint, freeze or owner authority of `token_program`'s accounts.
+pub fn create_multisig(
+    client: &RpcClient,
+    payer: &Keypair,
+    multisig: &Keypair,
+    signers: &[Pubkey],
+    m: u8,
+    token_program: &Pubkey,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instructions = create_multisig_instructions(
+        client,
+        &payer.pubkey(),
+        &multisig.pubkey(),
+        signers,
+        m,
+        token_program,
+    )?;
+
+    send(client, &instructions, &[payer, multisig], config)
+}
+
+/// Instructions of [`create_multisig`], signed by `payer` and `multisig`.
+pub fn create_multisig_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    multisig: &Pubkey,
+    signers: &[Pubkey],
+    m: u8,
+    token_program: &Pubkey,
+) -> Result<Vec<Instruction>, TokenError> {
+    let rent = client.get_minimum_balance_for_rent_exemption(Multisig::LEN)?;
+    let signers: Vec<_> = signers.iter().collect();
+    Ok(vec![
+        system_instruction::create_account(
+            payer,
+            multisig,
+            rent,
+            Multisig::LEN as u64,
+            token_program,
+        ),
+        instruction::initialize_multisig(token_program, multisig, &signers, m)?,
+    ])
+}
+
+/// Reads a multisig of either token program.
+pub fn get_multisig(
+    client: &RpcClient,
+    multisig: &Pubkey,
+) -> Result<Multisig, TokenError> {
+    let account = client.get_account(multisig)?;
+    if !is_token_program(&account.owner) {
+        return Err(TokenError::NotToken(*multisig));
+    }
+    Ok(Multisig::unpack(&account.data)?)
+}
+
+/// The signers of a multisig, which are only valid in the first `n` slots.
+pub fn multisig_signers(multisig: &Multisig) -> &[Pubkey] {
+    &multisig.signers[..usize::from(multisig.n)]
+}
+
+/// Mints `amount` base units of `mint` to `destination`, authorized by
+/// `signers` on behalf of the `authority` multisig. Each of `signers` has to
+/// sign the transaction.
+pub fn mint_to_instruction(
+    client: &RpcClient,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    signers: &[Pubkey],
+    amount: u64,
+) -> Result<Instruction, TokenError> {
+    let program = token_program(client, mint)?;
+    let signers: Vec<_> = signers.iter().collect();
+
+    Ok(instruction::mint_to(
+        &program,
+        mint,
+        destination,
+        authority,
+        &signers,
+        amount,
+    )?)
+}
+
+/// Replaces the `authority_type` authority of a mint or token account,
+/// removing it when `new_authority` is `None`. Pass the multisig members
+/// signing as `signers`, or nothing when `authority` signs itself.
+pub fn set_authority_instruction(
+    client: &RpcClient,
+    account: &Pubkey,
+    new_authority: Option<&Pubkey>,
+    authority_type: AuthorityType,
+    authority: &Pubkey,
+    signers: &[Pubkey],
+) -> Result<Instruction, TokenError> {
+    let program = token_program(client, account)?;
+    let signers: Vec<_> = signers.iter().collect();
+
+    Ok(instruction::set_authority(
+        &program,
+        account,
+        new_authority,
+        authority_type,
+        authority,
+        &signers,
+    )?)
+}
+
+/// How far a transaction is from satisfying one multisig it uses.
+#[derive(Clone, Debug)]
+pub struct MultisigStatus {
+    pub multisig: Pubkey,
+    /// Signatures required by the multisig.
+    pub m: u8,
+    /// Members that signed the transaction.
+    pub signed: Vec<Pubkey>,
+    /// Members the transaction expects a signature from that is still
+    /// missing.
+    pub pending: Vec<Pubkey>,
+    /// Members the transaction does not ask to sign.
+    pub absent: Vec<Pubkey>,
+}
+
+impl MultisigStatus {
+    pub fn is_satisfied(&self) -> bool {
+        self.signed.len() >= usize::from(self.m)
+    }
+}
+
+/// Signing progress of every multisig among the transaction's accounts.
+pub fn multisig_status(
+    client: &RpcClient,
+    transaction: &Transaction,
+) -> Result<Vec<MultisigStatus>, TokenError> {
+    let account_keys = &transaction.message.account_keys;
+    let accounts = client.get_multiple_accounts(account_keys)?;
+
+    Ok(account_keys
+        .iter()
+        .zip(accounts)
+        .filter_map(|(pubkey, account)| {
+            let account = account?;
+            if !is_token_program(&account.owner) {
+                return None;
+            }
+            let multisig = unpack_multisig(&account.data)?;
+            Some(signing_status(transaction, pubkey, &multisig))
+        })
+        .collect())
+}
+
+/// Sorts the members of `multisig` by whether they signed `transaction`,
+/// are asked to but have not yet, or are not asked to sign at all.
+pub fn signing_status(
+    transaction: &Transaction,
+    pubkey: &Pubkey,
+    multisig: &Multisig,
+) -> MultisigStatus {
+    let message = &transaction.message;
+    let num_signers = usize::from(message.header.num_required_signatures);
+    let signers = &message.account_keys[..num_signers];
+
+    let mut status = MultisigStatus {
+        multisig: *pubkey,
+        m: multisig.m,
+        signed: Vec::new(),
+        pending: Vec::new(),
+        absent: Vec::new(),
+    };
+    for member in multisig_signers(multisig) {
+        let signature = signers
+            .iter()
+            .position(|signer| signer == member)
+            .map(|i| transaction.signatures[i]);
+        match signature {
+            Some(signature) if signature != Signature::default() => {
+                status.signed.push(*member)
+            }
+            Some(_) => status.pending.push(*member),
+            None => status.absent.push(*member),
+        }
+    }
+    status
+}
+
+/// Reads token program account data as a multisig, or `None` if it holds
+/// something else.
+///
+/// Token-2022 pads mints and accounts with extensions so they are never as
+/// long as a multisig. Should one be, its account type at offset 165 falls
+/// in a signer slot, so on top of the size, `m` and `n` have to describe a
+/// valid multisig and the slots past the `n` members have to be zeroed.
+pub fn unpack_multisig(data: &[u8]) -> Option<Multisig> {
+    if data.len() != Multisig::LEN {
+        return None;
+    }
+    let multisig = Multisig::unpack(data).ok()?;
+    let n = usize::from(multisig.n);
+    if multisig.m == 0 || multisig.m > multisig.n || n > MAX_SIGNERS {
+        return None;
+    }
+    let unused = &multisig.signers[n..];
+    unused.iter().all(|signer| *signer == Pubkey::default()).then_some(multisig)
+}
+
+/// Parses the kebab case name of an authority type, such as `mint-tokens`.
+pub fn parse_authority_type(s: &str) -> Result<AuthorityType, String> {
+    Ok(match s {
+        "mint-tokens" => AuthorityType::MintTokens,
+        "freeze-account" => AuthorityType::FreezeAccount,
+        "account-owner" => AuthorityType::AccountOwner,
+        "close-account" => AuthorityType::CloseAccount,
+        "transfer-fee-config" => AuthorityType::TransferFeeConfig,
+        "withheld-withdraw" => AuthorityType::WithheldWithdraw,
+        "close-mint" => AuthorityType::CloseMint,
+        "interest-rate" => AuthorityType::InterestRate,
+        "permanent-delegate" => AuthorityType::PermanentDelegate,
+        _ => return Err(format!("unknown authority type `{s}`")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, message::Message};
+    use spl_token_2022::{
+        extension::{
+            immutable_owner::ImmutableOwner, BaseStateWithExtensions,
+            ExtensionType, StateWithExtensionsMut,
+        },
+        state::Account,
+    };
+
+    fn multisig(m: u8, members: &[Pubkey]) -> Multisig {
+        let mut signers = [Pubkey::default(); MAX_SIGNERS];
+        signers[..members.len()].copy_from_slice(members);
+        Multisig {
+            m,
+            n: members.len() as u8,
+            is_initialized: true,
+            signers,
+        }
+    }
+
+    fn packed(multisig: &Multisig) -> Vec<u8> {
+        let mut data = vec![0; Multisig::LEN];
+        Multisig::pack(*multisig, &mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn classifies_members() {
+        let payer = Keypair::new();
+        let signer = Keypair::new();
+        let pending = Pubkey::new_unique();
+        let absent = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let multisig = multisig(2, &[signer.pubkey(), pending, absent]);
+        let instruction = instruction::mint_to(
+            &spl_token_2022::id(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &authority,
+            &[&signer.pubkey(), &pending],
+            1,
+        )
+        .unwrap();
+        let mut transaction = Transaction::new_unsigned(Message::new(
+            &[instruction],
+            Some(&payer.pubkey()),
+        ));
+        transaction
+            .try_partial_sign(&[&payer, &signer], Hash::default())
+            .unwrap();
+
+        let status = signing_status(&transaction, &authority, &multisig);
+
+        assert_eq!(status.multisig, authority);
+        assert_eq!(status.signed, [signer.pubkey()]);
+        assert_eq!(status.pending, [pending]);
+        assert_eq!(status.absent, [absent]);
+        assert!(!status.is_satisfied());
+    }
+
+    #[test]
+    fn only_unpacks_valid_multisigs() {
+        let members = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let valid = multisig(2, &members);
+        assert_eq!(unpack_multisig(&packed(&valid)), Some(valid));
+
+        assert_eq!(unpack_multisig(&packed(&valid)[..300]), None);
+        assert_eq!(unpack_multisig(&packed(&multisig(3, &members))), None);
+        assert_eq!(unpack_multisig(&packed(&multisig(0, &members))), None);
+        let uninitialized = Multisig {
+            is_initialized: false,
+            ..valid
+        };
+        assert_eq!(unpack_multisig(&packed(&uninitialized)), None);
+    }
+
+    #[test]
+    fn token_2022_accounts_are_not_multisigs() {
+        // A token account with extensions forced to the multisig size, its
+        // base starts with a mint whose bytes read as a valid `m` and `n`.
+        let mut data = vec![0; Multisig::LEN];
+        let len = ExtensionType::get_account_len::<Account>(&[
+            ExtensionType::ImmutableOwner,
+        ]);
+        let mut state =
+            StateWithExtensionsMut::<Account>::unpack_uninitialized(
+                &mut data[..len],
+            )
+            .unwrap();
+        state.init_extension::<ImmutableOwner>(true).unwrap();
+        state.base = Account {
+            mint: Pubkey::new_from_array([1; 32]),
+            state: spl_token_2022::state::AccountState::Initialized,
+            ..Account::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        assert!(state.get_extension_types().is_ok());
+
+        assert_eq!(unpack_multisig(&data), None);
+    }
+
+    #[test]
+    fn lists_only_the_first_n_signers() {
+        let members = [Pubkey::new_unique(), Pubkey::new_unique()];
+        assert_eq!(multisig_signers(&multisig(1, &members)), members);
+        assert!(multisig_signers(&multisig(1, &[])).is_empty());
+    }
+
+    #[test]
+    fn parses_authority_types() {
+        assert_eq!(
+            parse_authority_type("mint-tokens"),
+            Ok(AuthorityType::MintTokens)
+        );
+        assert_eq!(
+            parse_authority_type("close-mint"),
+            Ok(AuthorityType::CloseMint)
+        );
+        assert_eq!(
+            parse_authority_type("permanent-delegate"),
+            Ok(AuthorityType::PermanentDelegate)
+        );
+        assert_eq!(
+            parse_authority_type("MintTokens"),
+            Err("unknown authority type `MintTokens`".to_string())
+        );
+    }
+}
@@ -0,0 +1,444 @@
+use crate::{
+    budget::{
+        send_with_compute_budget, with_compute_budget, ComputeBudgetConfig,
+    },
+    keypair::unique_signers,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_client::{
+    client_error::{ClientError, Result as ClientResult},
+    nonce_utils,
+    rpc_client::RpcClient,
+};
+use solana_sdk::{
+    instruction::Instruction,
+    message::Message,
+    nonce::{self, state::Data as NonceData},
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{keypair::Keypair, Signer, SignerError},
+    system_instruction,
+    transaction::Transaction,
+};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum OfflineError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    #[error("invalid nonce account: {0}")]
+    Nonce(#[from] nonce_utils::Error),
+
+    #[error("invalid encoded transaction: {0}")]
+    Decode(String),
+
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+
+    #[error("transaction is still missing signatures from {}", join(.0))]
+    MissingSignatures(Vec<Pubkey>),
+
+    #[error("the transaction does not ask {} to sign", join(.0))]
+    NotASigner(Vec<Pubkey>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionEncoding {
+    Base58,
+    Base64,
+}
+
+impl FromStr for TransactionEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "base58" => Ok(Self::Base58),
+            "base64" => Ok(Self::Base64),
+            _ => Err(format!("unknown transaction encoding `{s}`")),
+        }
+    }
+}
+
+/// Creates a rent exempt nonce account controlled by `authority`.
+pub fn create_nonce_account(
+    client: &RpcClient,
+    payer: &Keypair,
+    nonce_account: &Keypair,
+    authority: &Pubkey,
+    budget: &ComputeBudgetConfig,
+) -> ClientResult<Signature> {
+    let instructions = create_nonce_account_instructions(
+        client,
+        &payer.pubkey(),
+        &nonce_account.pubkey(),
+        authority,
+    )?;
+
+    send_with_compute_budget(
+        client,
+        &instructions,
+        &payer.pubkey(),
+        &[payer, nonce_account],
+        budget,
+    )
+}
+
+/// Instructions funding and initializing a rent exempt nonce account.
+pub fn create_nonce_account_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    authority: &Pubkey,
+) -> ClientResult<Vec<Instruction>> {
+    let lamports =
+        client.get_minimum_balance_for_rent_exemption(nonce::State::size())?;
+
+    Ok(system_instruction::create_nonce_account(
+        payer,
+        nonce_account,
+        authority,
+        lamports,
+    ))
+}
+
+/// Reads the stored nonce and its authority.
+pub fn get_nonce(
+    client: &RpcClient,
+    nonce_account: &Pubkey,
+) -> Result<NonceData, OfflineError> {
+    let account = nonce_utils::get_account_with_commitment(
+        client,
+        nonce_account,
+        client.commitment(),
+    )?;
+
+    Ok(nonce_utils::data_from_account(&account)?)
+}
+
+/// Replaces the stored nonce, invalidating transactions built against the
+/// old one.
+pub fn advance_nonce(
+    client: &RpcClient,
+    payer: &Keypair,
+    nonce_account: &Pubkey,
+    authority: &Keypair,
+    budget: &ComputeBudgetConfig,
+) -> ClientResult<Signature> {
+    let instruction = system_instruction::advance_nonce_account(
+        nonce_account,
+        &authority.pubkey(),
+    );
+
+    send_with_compute_budget(
+        client,
+        &[instruction],
+        &payer.pubkey(),
+        &unique_signers(&[payer, authority]),
+        budget,
+    )
+}
+
+/// Withdraws lamports from a nonce account, closing it when everything is
+/// withdrawn.
+pub fn withdraw_from_nonce(
+    client: &RpcClient,
+    payer: &Keypair,
+    nonce_account: &Pubkey,
+    authority: &Keypair,
+    to: &Pubkey,
+    lamports: u64,
+    budget: &ComputeBudgetConfig,
+) -> ClientResult<Signature> {
+    let instruction = system_instruction::withdraw_nonce_account(
+        nonce_account,
+        &authority.pubkey(),
+        to,
+        lamports,
+    );
+
+    send_with_compute_budget(
+        client,
+        &[instruction],
+        &payer.pubkey(),
+        &unique_signers(&[payer, authority]),
+        budget,
+    )
+}
+
+/// Builds an unsigned transaction that uses the durable nonce stored in
+/// `nonce_account` instead of a recent blockhash, so it can be signed offline
+/// and broadcast any time before the nonce is advanced.
+pub fn build_nonce_transaction(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+    nonce_account: &Pubkey,
+    budget: &ComputeBudgetConfig,
+) -> Result<Transaction, OfflineError> {
+    let nonce = get_nonce(client, nonce_account)?;
+    let instructions =
+        with_compute_budget(client, instructions, fee_payer, budget)?;
+
+    let mut message = Message::new_with_nonce(
+        instructions,
+        Some(fee_payer),
+        nonce_account,
+        &nonce.authority,
+    );
+    message.recent_blockhash = nonce.blockhash();
+
+    Ok(Transaction::new_unsigned(message))
+}
+
+pub fn encode_transaction(
+    transaction: &Transaction,
+    encoding: TransactionEncoding,
+) -> String {
+    let bytes =
+        bincode::serialize(transaction).expect("transaction serializes");
+    match encoding {
+        TransactionEncoding::Base58 => bs58::encode(bytes).into_string(),
+        TransactionEncoding::Base64 => BASE64.encode(bytes),
+    }
+}
+
+pub fn decode_transaction(
+    encoded: &str,
+    encoding: TransactionEncoding,
+) -> Result<Transaction, OfflineError> {
+    let bytes = match encoding {
+        TransactionEncoding::Base58 => bs58::decode(encoded.trim())
+            .into_vec()
+            .map_err(|e| OfflineError::Decode(e.to_string()))?,
+        TransactionEncoding::Base64 => BASE64
+            .decode(encoded.trim())
+            .map_err(|e| OfflineError::Decode(e.to_string()))?,
+    };
+
+    bincode::deserialize(&bytes)
+        .map_err(|e| OfflineError::Decode(e.to_string()))
+}
+
+/// Adds the signatures of those `signers` the transaction asks for, keeping
+/// the signatures already present, and returns who signed. Keypairs it does
+/// not ask for are skipped, so it only fails if none of them is a signer.
+pub fn sign_transaction(
+    transaction: &mut Transaction,
+    signers: &[Keypair],
+) -> Result<Vec<Pubkey>, OfflineError> {
+    let num_signers =
+        usize::from(transaction.message.header.num_required_signatures);
+    let required = &transaction.message.account_keys[..num_signers];
+    let signing: Vec<_> = signers
+        .iter()
+        .filter(|signer| required.contains(&signer.pubkey()))
+        .collect();
+    if signing.is_empty() {
+        return Err(OfflineError::NotASigner(
+            signers.iter().map(Keypair::pubkey).collect(),
+        ));
+    }
+
+    let blockhash = transaction.message.recent_blockhash;
+    transaction.try_partial_sign(&signing, blockhash)?;
+    Ok(signing.iter().map(|signer| signer.pubkey()).collect())
+}
+
+/// Signers whose signature is still missing.
+pub fn missing_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    let signers =
+        usize::from(transaction.message.header.num_required_signatures);
+
+    transaction
+        .message
+        .account_keys
+        .iter()
+        .zip(&transaction.signatures)
+        .take(signers)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(pubkey, _)| *pubkey)
+        .collect()
+}
+
+/// Sends a transaction signed offline, waiting for confirmation.
+pub fn broadcast_transaction(
+    client: &RpcClient,
+    transaction: &Transaction,
+) -> Result<Signature, OfflineError> {
+    let missing = missing_signers(transaction);
+    if !missing.is_empty() {
+        return Err(OfflineError::MissingSignatures(missing));
+    }
+
+    Ok(client.send_and_confirm_transaction(transaction)?)
+}
+
+fn join(pubkeys: &[Pubkey]) -> String {
+    pubkeys.iter().map(Pubkey::to_string).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_account_decoder::{UiAccount, UiAccountEncoding};
+    use solana_client::rpc_request::RpcRequest;
+    use solana_sdk::{
+        account::Account,
+        hash::Hash,
+        nonce::state::{DurableNonce, State, Versions},
+        system_program,
+    };
+    use std::collections::HashMap;
+
+    fn transfer(from: &Pubkey, fee_payer: &Pubkey) -> Transaction {
+        let instruction =
+            system_instruction::transfer(from, &Pubkey::new_unique(), 1);
+        let mut message = Message::new(&[instruction], Some(fee_payer));
+        message.recent_blockhash = Hash::new_unique();
+        Transaction::new_unsigned(message)
+    }
+
+    #[test]
+    fn signing_skips_keypairs_not_asked_for() {
+        let fee_payer = Keypair::new();
+        let from = Keypair::new();
+        let stranger = Keypair::new();
+        let mut transaction = transfer(&from.pubkey(), &fee_payer.pubkey());
+
+        let signed = sign_transaction(
+            &mut transaction,
+            &[stranger.insecure_clone(), from.insecure_clone()],
+        )
+        .unwrap();
+
+        assert_eq!(signed, [from.pubkey()]);
+        assert_eq!(missing_signers(&transaction), [fee_payer.pubkey()]);
+        assert!(matches!(
+            sign_transaction(&mut transaction, &[stranger.insecure_clone()]),
+            Err(OfflineError::NotASigner(keys)) if keys == [stranger.pubkey()]
+        ));
+    }
+
+    #[test]
+    fn encodings_round_trip() {
+        let fee_payer = Keypair::new();
+        let mut transaction =
+            transfer(&fee_payer.pubkey(), &fee_payer.pubkey());
+        sign_transaction(&mut transaction, &[fee_payer]).unwrap();
+
+        for encoding in
+            [TransactionEncoding::Base58, TransactionEncoding::Base64]
+        {
+            let encoded = encode_transaction(&transaction, encoding);
+            let decoded =
+                decode_transaction(&format!("{encoded}\n"), encoding).unwrap();
+            assert_eq!(decoded, transaction);
+        }
+        assert!(matches!(
+            decode_transaction("not base64!", TransactionEncoding::Base64),
+            Err(OfflineError::Decode(_))
+        ));
+        assert_eq!(
+            "Base58".parse::<TransactionEncoding>(),
+            Ok(TransactionEncoding::Base58)
+        );
+    }
+
+    #[test]
+    fn partial_signing_keeps_earlier_signatures() {
+        let fee_payer = Keypair::new();
+        let from = Keypair::new();
+        let mut transaction = transfer(&from.pubkey(), &fee_payer.pubkey());
+        assert_eq!(
+            missing_signers(&transaction),
+            [fee_payer.pubkey(), from.pubkey()]
+        );
+
+        sign_transaction(&mut transaction, &[from.insecure_clone()]).unwrap();
+        let from_signature = transaction.signatures[1];
+        sign_transaction(&mut transaction, &[fee_payer]).unwrap();
+
+        assert_eq!(transaction.signatures[1], from_signature);
+        assert!(missing_signers(&transaction).is_empty());
+        assert!(transaction.verify().is_ok());
+    }
+
+    #[test]
+    fn broadcast_needs_every_signature() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let fee_payer = Keypair::new();
+        let transaction = transfer(&fee_payer.pubkey(), &fee_payer.pubkey());
+
+        assert!(matches!(
+            broadcast_transaction(&client, &transaction),
+            Err(OfflineError::MissingSignatures(keys))
+                if keys == [fee_payer.pubkey()]
+        ));
+    }
+
+    #[test]
+    fn nonce_transactions_advance_the_nonce_first() {
+        let nonce_account = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let fee_payer = Pubkey::new_unique();
+        let durable_nonce = DurableNonce::from_blockhash(&Hash::new_unique());
+        let state = Versions::new(State::Initialized(NonceData::new(
+            authority,
+            durable_nonce,
+            5_000,
+        )));
+        let account = Account {
+            lamports: 1_000_000,
+            data: bincode::serialize(&state).unwrap(),
+            owner: system_program::id(),
+            ..Account::default()
+        };
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            json!({
+                "context": { "slot": 1 },
+                "value": UiAccount::encode(
+                    &nonce_account,
+                    &account,
+                    UiAccountEncoding::Base64,
+                    None,
+                    None,
+                ),
+            }),
+        );
+        let client =
+            RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        let instruction =
+            system_instruction::transfer(&fee_payer, &Pubkey::new_unique(), 1);
+
+        let transaction = build_nonce_transaction(
+            &client,
+            &[instruction],
+            &fee_payer,
+            &nonce_account,
+            &ComputeBudgetConfig::default(),
+        )
+        .unwrap();
+
+        let message = &transaction.message;
+        assert_eq!(message.recent_blockhash, *durable_nonce.as_hash());
+        let advance = &message.instructions[0];
+        assert_eq!(
+            message.account_keys[usize::from(advance.program_id_index)],
+            system_program::id()
+        );
+        assert_eq!(
+            advance.data,
+            bincode::serialize(
+                &system_instruction::SystemInstruction::AdvanceNonceAccount
+            )
+            .unwrap()
+        );
+        assert_eq!(missing_signers(&transaction), [fee_payer, authority]);
+    }
+}
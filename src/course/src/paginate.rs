@@ -0,0 +1,315 @@
+use crate::{
+    fetch::{fetch_accounts, FetchConfig},
+    intro::{StudentIntro, NAME_LEN_OFFSET},
+    query::IntroQuery,
+};
+use borsh::BorshDeserialize;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    client_error::Result as ClientResult,
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+};
+use solana_sdk::pubkey::Pubkey;
+use std::{fmt, str::FromStr};
+
+/// Number of name bytes prefetched for sorting. Names sharing a longer prefix
+/// fall back to ordering by pubkey.
+pub const SORT_KEY_LEN: usize = 32;
+
+/// Position right after the last item of a page, used to ask for the next one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cursor(pub Pubkey);
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = <Pubkey as FromStr>::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pubkey::from_str(s).map(Cursor)
+    }
+}
+
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 1-based page number.
+    pub page: usize,
+    pub total_pages: usize,
+    pub total_items: usize,
+    pub next_cursor: Option<Cursor>,
+}
+
+/// Pages through student intros sorted case-insensitively by name.
+///
+/// The sorted key list is fetched once, using a data slice that only covers
+/// the name, and reused until [`refresh`](Self::refresh) is called.
+pub struct IntroPaginator<'a> {
+    client: &'a RpcClient,
+    program_id: Pubkey,
+    query: IntroQuery,
+    per_page: usize,
+    fetch_config: FetchConfig,
+    keys: Option<Vec<Pubkey>>,
+}
+
+impl<'a> IntroPaginator<'a> {
+    pub fn new(
+        client: &'a RpcClient,
+        program_id: Pubkey,
+        query: IntroQuery,
+        per_page: usize,
+    ) -> Self {
+        Self {
+            client,
+            program_id,
+            query,
+            per_page: per_page.max(1),
+            fetch_config: FetchConfig::default(),
+            keys: None,
+        }
+    }
+
+    pub fn with_fetch_config(mut self, fetch_config: FetchConfig) -> Self {
+        self.fetch_config = fetch_config;
+        self
+    }
+
+    /// Drops the cached key list so the next page refetches it.
+    pub fn refresh(&mut self) {
+        self.keys = None;
+    }
+
+    /// Returns the 1-based `page`. Pages past the end are empty.
+    pub async fn page(
+        &mut self,
+        page: usize,
+    ) -> ClientResult<Page<(Pubkey, StudentIntro)>> {
+        let start = match page {
+            0 => usize::MAX,
+            page => (page - 1).saturating_mul(self.per_page),
+        };
+        self.page_from(start, page).await
+    }
+
+    /// Returns the page that follows `cursor`. An unknown cursor, e.g. one
+    /// whose account was closed since the keys were cached, gives an empty
+    /// page.
+    pub async fn page_after(
+        &mut self,
+        cursor: &Cursor,
+    ) -> ClientResult<Page<(Pubkey, StudentIntro)>> {
+        let start = self
+            .keys()
+            .await?
+            .iter()
+            .position(|key| *key == cursor.0)
+            .map_or(usize::MAX, |i| i + 1);
+        let page = (start / self.per_page).saturating_add(1);
+        self.page_from(start, page).await
+    }
+
+    async fn page_from(
+        &mut self,
+        start: usize,
+        page: usize,
+    ) -> ClientResult<Page<(Pubkey, StudentIntro)>> {
+        let per_page = self.per_page;
+        let keys = self.keys().await?;
+        let total_items = keys.len();
+        let total_pages = total_items.div_ceil(per_page);
+
+        let (page_keys, next_cursor) = page_keys(keys, start, per_page);
+        let page_keys = page_keys.to_vec();
+
+        let items = if page_keys.is_empty() {
+            Vec::new()
+        } else {
+            self.fetch(&page_keys).await?
+        };
+
+        Ok(Page {
+            items,
+            page,
+            total_pages,
+            total_items,
+            next_cursor,
+        })
+    }
+
+    async fn keys(&mut self) -> ClientResult<&[Pubkey]> {
+        if self.keys.is_none() {
+            self.keys = Some(
+                prefetch_accounts(self.client, &self.program_id, &self.query)
+                    .await?,
+            );
+        }
+        Ok(self.keys.as_deref().unwrap_or_default())
+    }
+
+    async fn fetch(
+        &self,
+        keys: &[Pubkey],
+    ) -> ClientResult<Vec<(Pubkey, StudentIntro)>> {
+        let accounts =
+            fetch_accounts(self.client, keys, self.fetch_config).await?;
+        let mut items = Vec::with_capacity(keys.len());
+
+        for (pubkey, account) in keys.iter().zip(accounts) {
+            if let Some(account) = account {
+                let data =
+                    StudentIntro::deserialize(&mut account.data.as_slice())?;
+                items.push((*pubkey, data));
+            }
+        }
+
+        Ok(items)
+    }
+}
+
+/// Keys of the student intros matching `query`, sorted case-insensitively
+/// by name. Only the start of each name is fetched.
+pub async fn prefetch_accounts(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    query: &IntroQuery,
+) -> ClientResult<Vec<Pubkey>> {
+    let config = RpcProgramAccountsConfig {
+        filters: query.rpc_filters(),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: NAME_LEN_OFFSET,
+                length: 4 + SORT_KEY_LEN,
+            }),
+            ..RpcAccountInfoConfig::default()
+        },
+        with_context: None,
+    };
+
+    let accounts = client
+        .get_program_accounts_with_config(program_id, config)
+        .await?
+        .into_iter()
+        .map(|(pubkey, account)| (pubkey, account.data))
+        .collect();
+    Ok(sort_by_name(accounts))
+}
+
+/// Fetches the 1-based `page` of student intros matching `query`, sorted by
+/// name. Use an [`IntroPaginator`] to fetch several pages without refetching
+/// the key list.
+pub async fn fetch_page(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    query: &IntroQuery,
+    page: usize,
+    per_page: usize,
+) -> ClientResult<Page<(Pubkey, StudentIntro)>> {
+    IntroPaginator::new(client, *program_id, query.clone(), per_page)
+        .page(page)
+        .await
+}
+
+/// Keys of the page starting at index `start`, and the cursor of the page
+/// after it if there is one. A `start` past the end gives an empty page.
+fn page_keys(
+    keys: &[Pubkey],
+    start: usize,
+    per_page: usize,
+) -> (&[Pubkey], Option<Cursor>) {
+    let page_keys = keys
+        .get(start..)
+        .map(|rest| &rest[..rest.len().min(per_page)])
+        .unwrap_or_default();
+
+    let next_cursor = page_keys
+        .last()
+        .filter(|_| start + page_keys.len() < keys.len())
+        .map(|key| Cursor(*key));
+
+    (page_keys, next_cursor)
+}
+
+/// Orders accounts by the name in their data slice, then by pubkey.
+fn sort_by_name(accounts: Vec<(Pubkey, Vec<u8>)>) -> Vec<Pubkey> {
+    let mut accounts = accounts
+        .into_iter()
+        .map(|(pubkey, data)| (sort_key(&data), pubkey))
+        .collect::<Vec<_>>();
+
+    accounts.sort();
+    accounts.into_iter().map(|(_, pubkey)| pubkey).collect()
+}
+
+/// Lowercased name read from a data slice starting at the name length
+/// prefix. The name may be truncated to [`SORT_KEY_LEN`] bytes.
+fn sort_key(slice: &[u8]) -> String {
+    let Some((len, name)) = slice.split_first_chunk::<4>() else {
+        return String::new();
+    };
+    let len = (u32::from_le_bytes(*len) as usize).min(name.len());
+
+    String::from_utf8_lossy(&name[..len]).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_slice(name: &str) -> Vec<u8> {
+        let mut data = (name.len() as u32).to_le_bytes().to_vec();
+        data.extend_from_slice(name.as_bytes());
+        data.truncate(4 + SORT_KEY_LEN);
+        data
+    }
+
+    #[test]
+    fn sorts_case_insensitively_then_by_pubkey() {
+        let keys: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let sorted = sort_by_name(vec![
+            (keys[0], name_slice("carol")),
+            (keys[1], name_slice("Bob")),
+            (keys[2], name_slice("alice")),
+            (keys[3], name_slice("bob")),
+        ]);
+
+        assert_eq!(sorted, [keys[2], keys[1], keys[3], keys[0]]);
+    }
+
+    #[test]
+    fn sort_key_handles_truncated_and_short_slices() {
+        let long = "x".repeat(SORT_KEY_LEN + 10);
+        assert_eq!(sort_key(&name_slice(&long)), "x".repeat(SORT_KEY_LEN));
+        assert_eq!(sort_key(&[1, 0]), "");
+    }
+
+    #[test]
+    fn pages_keys_with_cursor() {
+        let keys: Vec<_> = (0..5).map(|_| Pubkey::new_unique()).collect();
+
+        let (page, cursor) = page_keys(&keys, 0, 2);
+        assert_eq!(page, &keys[..2]);
+        assert_eq!(cursor, Some(Cursor(keys[1])));
+
+        let (page, cursor) = page_keys(&keys, 4, 2);
+        assert_eq!(page, &keys[4..]);
+        assert_eq!(cursor, None);
+    }
+
+    #[test]
+    fn out_of_range_page_is_empty() {
+        let keys: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+
+        for start in [3, 10, usize::MAX] {
+            let (page, cursor) = page_keys(&keys, start, 2);
+            assert!(page.is_empty());
+            assert_eq!(cursor, None);
+        }
+    }
+}
@@ -0,0 +1,710 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_sdk::{
+    hash::hashv,
+    instruction::Instruction,
+    message::Message,
+    native_token::LAMPORTS_PER_SOL,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{keypair::Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread::sleep,
+    time::Duration,
+};
+use thiserror::Error;
+
+/// Wait between status checks of a batch that landed but is not final yet.
+const RECOVER_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Error)]
+pub enum PayoutError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid journal entry: {0}")]
+    Journal(String),
+
+    #[error("manifest line {line}: {reason}")]
+    Manifest {
+        line: usize,
+        reason: String,
+    },
+
+    #[error(
+        "batch {batch} does not match the journal, was the manifest edited?"
+    )]
+    ManifestChanged {
+        batch: usize,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Payout {
+    pub recipient: Pubkey,
+    pub lamports: u64,
+}
+
+/// Reads `recipient,amount_sol` rows. A leading header row is skipped.
+pub fn read_manifest<R: Read>(
+    mut reader: R,
+) -> Result<Vec<Payout>, PayoutError> {
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(contents.as_slice());
+
+    let mut payouts = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        let line = record
+            .position()
+            .map_or(i + 1, |position| line_number(&contents, position));
+        let invalid = |reason: String| PayoutError::Manifest {
+            line,
+            reason,
+        };
+
+        let (Some(recipient), Some(amount), None) =
+            (record.get(0), record.get(1), record.get(2))
+        else {
+            return Err(invalid("expected `recipient,amount_sol`".into()));
+        };
+
+        if i == 0 && recipient.eq_ignore_ascii_case("recipient") {
+            continue;
+        }
+
+        let recipient = Pubkey::from_str(recipient)
+            .map_err(|e| invalid(format!("invalid recipient: {e}")))?;
+        let lamports = parse_sol(amount)
+            .ok_or_else(|| invalid(format!("invalid amount `{amount}`")))?;
+
+        payouts.push(Payout {
+            recipient,
+            lamports,
+        });
+    }
+
+    Ok(payouts)
+}
+
+/// Line a record starts on. The csv reader positions a record before the
+/// blank and comment lines preceding it, so those are skipped here.
+fn line_number(contents: &[u8], position: &csv::Position) -> usize {
+    let offset = (position.byte() as usize).min(contents.len());
+    let before = contents[..offset].iter().filter(|&&b| b == b'\n').count();
+    let skipped = contents[offset..]
+        .split(|&b| b == b'\n')
+        .take_while(|line| {
+            line.starts_with(b"#") || line.iter().all(u8::is_ascii_whitespace)
+        })
+        .count();
+
+    before + skipped + 1
+}
+
+/// Parses a decimal SOL amount into lamports without going through `f64`.
+pub fn parse_sol(amount: &str) -> Option<u64> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > 9 || (whole.is_empty() && fraction.is_empty()) {
+        return None;
+    }
+
+    let whole = match whole {
+        "" => 0,
+        whole => whole.parse::<u64>().ok()?,
+    };
+    let fraction = match fraction {
+        "" => 0,
+        fraction => format!("{fraction:0<9}").parse::<u64>().ok()?,
+    };
+
+    whole.checked_mul(LAMPORTS_PER_SOL)?.checked_add(fraction)
+}
+
+/// Transfers sent together in one transaction.
+#[derive(Clone, Debug)]
+pub struct Batch {
+    pub index: usize,
+    pub payouts: Vec<Payout>,
+}
+
+impl Batch {
+    pub fn instructions(&self, payer: &Pubkey) -> Vec<Instruction> {
+        self.payouts
+            .iter()
+            .map(|p| {
+                system_instruction::transfer(payer, &p.recipient, p.lamports)
+            })
+            .collect()
+    }
+
+    pub fn message(&self, payer: &Pubkey) -> Message {
+        Message::new(&self.instructions(payer), Some(payer))
+    }
+
+    pub fn lamports(&self) -> u64 {
+        self.payouts.iter().map(|p| p.lamports).sum()
+    }
+
+    /// Identifies the batch content, so a resumed run can tell if the
+    /// manifest changed under it.
+    pub fn digest(&self) -> String {
+        let bytes = self
+            .payouts
+            .iter()
+            .flat_map(|p| {
+                p.recipient
+                    .to_bytes()
+                    .into_iter()
+                    .chain(p.lamports.to_le_bytes())
+            })
+            .collect::<Vec<_>>();
+        hashv(&[&self.index.to_le_bytes(), &bytes]).to_string()
+    }
+}
+
+/// Packs as many transfers into each transaction as fit in a packet.
+pub fn pack_batches(payer: &Pubkey, payouts: &[Payout]) -> Vec<Batch> {
+    let mut batches: Vec<Batch> = Vec::new();
+
+    for payout in payouts {
+        if let Some(batch) = batches.last_mut() {
+            batch.payouts.push(*payout);
+            if transaction_size(&batch.message(payer)) <= PACKET_DATA_SIZE {
+                continue;
+            }
+            batch.payouts.pop();
+        }
+
+        batches.push(Batch {
+            index: batches.len(),
+            payouts: vec![*payout],
+        });
+    }
+
+    batches
+}
+
+fn transaction_size(message: &Message) -> usize {
+    let transaction = Transaction::new_unsigned(message.clone());
+    bincode::serialized_size(&transaction)
+        .map_or(usize::MAX, |size| size as usize)
+}
+
+#[derive(Debug, Default)]
+pub struct PayoutEstimate {
+    pub batches: usize,
+    pub transfers: usize,
+    pub lamports: u64,
+    pub fees: u64,
+    /// Batches an earlier run signed that turned out to have landed, which
+    /// are not counted in the totals.
+    pub landed: usize,
+}
+
+/// Totals the transfers and network fees of the batches that are still
+/// unpaid. Batches the journal has signed but not confirmed are looked up
+/// first, and only counted if they did not land.
+pub fn estimate(
+    client: &RpcClient,
+    payer: &Pubkey,
+    batches: &[Batch],
+    journal: &Journal,
+) -> Result<PayoutEstimate, PayoutError> {
+    let blockhash = client.get_latest_blockhash()?;
+    let mut estimate = PayoutEstimate::default();
+
+    for batch in batches.iter().filter(|b| !journal.is_confirmed(b)) {
+        if journal.signed_batch_landed(client, batch)? {
+            estimate.landed += 1;
+            continue;
+        }
+
+        let mut message = batch.message(payer);
+        message.recent_blockhash = blockhash;
+
+        estimate.batches += 1;
+        estimate.transfers += batch.payouts.len();
+        estimate.lamports += batch.lamports();
+        estimate.fees += client.get_fee_for_message(&message)?;
+    }
+
+    Ok(estimate)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum JournalEntry {
+    Signed {
+        batch: usize,
+        digest: String,
+        signature: String,
+        last_valid_block_height: u64,
+        /// Base64 bincode of the signed transaction, rebroadcast as-is on
+        /// resume while its blockhash is still valid.
+        transaction: String,
+    },
+    Confirmed {
+        batch: usize,
+        digest: String,
+        signature: String,
+    },
+}
+
+impl JournalEntry {
+    fn batch(&self) -> usize {
+        match self {
+            JournalEntry::Signed {
+                batch,
+                ..
+            }
+            | JournalEntry::Confirmed {
+                batch,
+                ..
+            } => *batch,
+        }
+    }
+
+    fn digest(&self) -> &str {
+        match self {
+            JournalEntry::Signed {
+                digest,
+                ..
+            }
+            | JournalEntry::Confirmed {
+                digest,
+                ..
+            } => digest,
+        }
+    }
+}
+
+/// Append-only NDJSON log of signed and confirmed batches.
+pub struct Journal {
+    path: PathBuf,
+    latest: HashMap<usize, JournalEntry>,
+}
+
+impl Journal {
+    /// Opens the journal at `path`, reading back any previous run.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, PayoutError> {
+        let path = path.as_ref().to_path_buf();
+        let mut latest = HashMap::new();
+
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry: JournalEntry = serde_json::from_str(&line)?;
+                latest.insert(entry.batch(), entry);
+            }
+        }
+
+        Ok(Self {
+            path,
+            latest,
+        })
+    }
+
+    pub fn is_confirmed(&self, batch: &Batch) -> bool {
+        matches!(
+            self.latest.get(&batch.index),
+            Some(entry @ JournalEntry::Confirmed { .. })
+                if entry.digest() == batch.digest()
+        )
+    }
+
+    /// Whether the transaction journaled for `batch` landed without error
+    /// at the client's commitment. Read only, nothing is rebroadcast.
+    fn signed_batch_landed(
+        &self,
+        client: &RpcClient,
+        batch: &Batch,
+    ) -> Result<bool, PayoutError> {
+        let Some(
+            entry @ JournalEntry::Signed {
+                signature,
+                ..
+            },
+        ) = self.latest.get(&batch.index)
+        else {
+            return Ok(false);
+        };
+        if entry.digest() != batch.digest() {
+            return Ok(false);
+        }
+
+        let signature = Signature::from_str(signature)
+            .map_err(|e| PayoutError::Journal(e.to_string()))?;
+        let status = client
+            .get_signature_statuses_with_history(&[signature])?
+            .value
+            .remove(0);
+        Ok(status.is_some_and(|status| {
+            status.err.is_none()
+                && status.satisfies_commitment(client.commitment())
+        }))
+    }
+
+    fn record(&mut self, entry: JournalEntry) -> Result<(), PayoutError> {
+        let mut file =
+            OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+
+        self.latest.insert(entry.batch(), entry);
+        Ok(())
+    }
+
+    fn confirm(
+        &mut self,
+        batch: &Batch,
+        signature: &Signature,
+    ) -> Result<(), PayoutError> {
+        self.record(JournalEntry::Confirmed {
+            batch: batch.index,
+            digest: batch.digest(),
+            signature: signature.to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchOutcome {
+    /// The journal already had the batch confirmed.
+    AlreadyPaid,
+    /// A transaction signed by an earlier run landed.
+    Recovered(Signature),
+    Sent(Signature),
+}
+
+/// Pays every batch the journal has not confirmed.
+///
+/// Each transaction is journaled before it is sent. On resume a signed but
+/// unconfirmed batch is looked up by signature and only re-signed once its
+/// blockhash has expired without it landing, so nobody is paid twice.
+pub fn run_payouts<F>(
+    client: &RpcClient,
+    payer: &Keypair,
+    batches: &[Batch],
+    journal: &mut Journal,
+    mut on_batch: F,
+) -> Result<(), PayoutError>
+where
+    F: FnMut(&Batch, BatchOutcome),
+{
+    for batch in batches {
+        let digest = batch.digest();
+        let previous = journal.latest.get(&batch.index);
+        if previous.is_some_and(|entry| entry.digest() != digest) {
+            return Err(PayoutError::ManifestChanged {
+                batch: batch.index,
+            });
+        }
+
+        match previous {
+            Some(JournalEntry::Confirmed {
+                ..
+            }) => {
+                on_batch(batch, BatchOutcome::AlreadyPaid);
+                continue;
+            }
+            Some(JournalEntry::Signed {
+                signature,
+                last_valid_block_height,
+                transaction,
+                ..
+            }) => {
+                let signature = Signature::from_str(signature)
+                    .map_err(|e| PayoutError::Journal(e.to_string()))?;
+                let last_valid_block_height = *last_valid_block_height;
+                let transaction = decode_transaction(transaction)?;
+
+                if let Some(signature) = recover(
+                    client,
+                    &signature,
+                    &transaction,
+                    last_valid_block_height,
+                )? {
+                    journal.confirm(batch, &signature)?;
+                    on_batch(batch, BatchOutcome::Recovered(signature));
+                    continue;
+                }
+            }
+            None => {}
+        }
+
+        let (blockhash, last_valid_block_height) =
+            client.get_latest_blockhash_with_commitment(client.commitment())?;
+        let transaction = Transaction::new_signed_with_payer(
+            &batch.instructions(&payer.pubkey()),
+            Some(&payer.pubkey()),
+            &[payer],
+            blockhash,
+        );
+        let signature = transaction.signatures[0];
+
+        journal.record(JournalEntry::Signed {
+            batch: batch.index,
+            digest,
+            signature: signature.to_string(),
+            last_valid_block_height,
+            transaction: encode_transaction(&transaction)?,
+        })?;
+
+        client.send_and_confirm_transaction(&transaction)?;
+        journal.confirm(batch, &signature)?;
+        on_batch(batch, BatchOutcome::Sent(signature));
+    }
+
+    Ok(())
+}
+
+/// Settles a batch signed by an earlier run. Returns the signature if it
+/// landed, `None` if it is safe to sign the batch again: either it failed,
+/// which rolls back every transfer in it, or its blockhash expired. A
+/// transaction seen below the client's commitment could still be dropped
+/// with its fork, so its status is polled until it is final.
+fn recover(
+    client: &RpcClient,
+    signature: &Signature,
+    transaction: &Transaction,
+    last_valid_block_height: u64,
+) -> Result<Option<Signature>, PayoutError> {
+    loop {
+        let status = client
+            .get_signature_statuses_with_history(&[*signature])?
+            .value
+            .remove(0);
+
+        match status {
+            Some(status)
+                if status.satisfies_commitment(client.commitment()) =>
+            {
+                return Ok(status.err.is_none().then_some(*signature));
+            }
+            Some(_) => sleep(RECOVER_POLL_INTERVAL),
+            None if client.get_block_height()? <= last_valid_block_height => {
+                client.send_and_confirm_transaction(transaction)?;
+                return Ok(Some(*signature));
+            }
+            None => return Ok(None),
+        }
+    }
+}
+
+fn encode_transaction(
+    transaction: &Transaction,
+) -> Result<String, PayoutError> {
+    let bytes = bincode::serialize(transaction)
+        .map_err(|e| PayoutError::Journal(e.to_string()))?;
+    Ok(BASE64.encode(bytes))
+}
+
+fn decode_transaction(encoded: &str) -> Result<Transaction, PayoutError> {
+    let bytes = BASE64
+        .decode(encoded)
+        .map_err(|e| PayoutError::Journal(e.to_string()))?;
+    bincode::deserialize(&bytes)
+        .map_err(|e| PayoutError::Journal(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payouts(count: usize) -> Vec<Payout> {
+        (0..count)
+            .map(|i| Payout {
+                recipient: Pubkey::new_unique(),
+                lamports: i as u64 + 1,
+            })
+            .collect()
+    }
+
+    fn journal_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "course-payout-{name}-{}.ndjson",
+            Pubkey::new_unique()
+        ))
+    }
+
+    #[test]
+    fn parses_sol_amounts() {
+        assert_eq!(parse_sol("1"), Some(LAMPORTS_PER_SOL));
+        assert_eq!(parse_sol("0.5"), Some(LAMPORTS_PER_SOL / 2));
+        assert_eq!(parse_sol(".000000001"), Some(1));
+        assert_eq!(parse_sol("2."), Some(2 * LAMPORTS_PER_SOL));
+        assert_eq!(parse_sol("1.123456789"), Some(1_123_456_789));
+    }
+
+    #[test]
+    fn rejects_invalid_sol_amounts() {
+        for amount in ["", ".", "-1", "1e3", "0.0000000001", "1.2.3", "abc"] {
+            assert_eq!(parse_sol(amount), None, "{amount}");
+        }
+        assert_eq!(parse_sol(&u64::MAX.to_string()), None);
+    }
+
+    #[test]
+    fn reads_manifest_with_header_and_comments() {
+        let recipient = Pubkey::new_unique();
+        let manifest = format!(
+            "recipient,amount_sol\n# first payout\n\n{recipient}, 1.5\n"
+        );
+
+        let payouts = read_manifest(manifest.as_bytes()).unwrap();
+        assert_eq!(
+            payouts,
+            [Payout {
+                recipient,
+                lamports: 1_500_000_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn manifest_errors_point_at_file_lines() {
+        let manifest = format!(
+            "# payouts\n\n{},1\n\n# bad amount below\n{},x\n",
+            Pubkey::new_unique(),
+            Pubkey::new_unique()
+        );
+
+        match read_manifest(manifest.as_bytes()) {
+            Err(PayoutError::Manifest {
+                line,
+                ..
+            }) => assert_eq!(line, 6),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn batches_fit_in_a_packet_and_keep_order() {
+        let payer = Pubkey::new_unique();
+        let payouts = payouts(50);
+        let batches = pack_batches(&payer, &payouts);
+
+        assert!(batches.len() > 1);
+        for (i, batch) in batches.iter().enumerate() {
+            assert_eq!(batch.index, i);
+            assert!(
+                transaction_size(&batch.message(&payer)) <= PACKET_DATA_SIZE
+            );
+        }
+        let packed: Vec<_> =
+            batches.iter().flat_map(|b| b.payouts.clone()).collect();
+        assert_eq!(packed, payouts);
+    }
+
+    #[test]
+    fn resumes_confirmed_batches_from_the_journal() {
+        let payer = Keypair::new();
+        let batches = pack_batches(&payer.pubkey(), &payouts(3));
+        let path = journal_path("resume");
+
+        let mut journal = Journal::open(&path).unwrap();
+        journal.confirm(&batches[0], &Signature::default()).unwrap();
+        let mut journal = Journal::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(journal.is_confirmed(&batches[0]));
+
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let mut outcomes = Vec::new();
+        run_payouts(
+            &client,
+            &payer,
+            &batches[..1],
+            &mut journal,
+            |_, outcome| outcomes.push(outcome),
+        )
+        .unwrap();
+        assert_eq!(outcomes, [BatchOutcome::AlreadyPaid]);
+    }
+
+    #[test]
+    fn refuses_to_resume_an_edited_manifest() {
+        let payer = Keypair::new();
+        let batches = pack_batches(&payer.pubkey(), &payouts(1));
+        let edited = pack_batches(&payer.pubkey(), &payouts(1));
+        let path = journal_path("edited");
+
+        let mut journal = Journal::open(&path).unwrap();
+        journal.confirm(&batches[0], &Signature::default()).unwrap();
+        let mut journal = Journal::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!journal.is_confirmed(&edited[0]));
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let result =
+            run_payouts(&client, &payer, &edited, &mut journal, |_, _| {});
+        assert!(matches!(
+            result,
+            Err(PayoutError::ManifestChanged {
+                batch: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn estimate_skips_signed_batches_that_landed() {
+        let payer = Keypair::new();
+        let batches = pack_batches(&payer.pubkey(), &payouts(3));
+        let path = journal_path("estimate");
+
+        let mut journal = Journal::open(&path).unwrap();
+        journal
+            .record(JournalEntry::Signed {
+                batch: 0,
+                digest: batches[0].digest(),
+                signature: Signature::default().to_string(),
+                last_valid_block_height: 0,
+                transaction: String::new(),
+            })
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let landed = RpcClient::new_mock("succeeds".to_string());
+        let totals =
+            estimate(&landed, &payer.pubkey(), &batches[..1], &journal)
+                .unwrap();
+        assert_eq!(totals.landed, 1);
+        assert_eq!(totals.batches, 0);
+        assert_eq!(totals.lamports, 0);
+
+        let failed = RpcClient::new_mock("instruction_error".to_string());
+        let totals =
+            estimate(&failed, &payer.pubkey(), &batches[..1], &journal)
+                .unwrap();
+        assert_eq!(totals.landed, 0);
+        assert_eq!(totals.batches, 1);
+        assert_eq!(totals.lamports, batches[0].lamports());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::intro::{NAME_LEN_OFFSET, NAME_OFFSET, VARIANT_OFFSET};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+
+/// Server-side filters over the Borsh layout of
+/// [`StudentIntro`](crate::intro::StudentIntro), so the RPC node only
+/// returns the matching accounts.
+#[derive(Clone, Debug, Default)]
+pub struct IntroQuery {
+    variant: Option<u8>,
+    name: Option<String>,
+    name_prefix: Option<String>,
+    data_size: Option<u64>,
+}
+
+impl IntroQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match accounts whose variant byte equals `variant`.
+    pub fn variant(mut self, variant: u8) -> Self {
+        self.variant = Some(variant);
+        self
+    }
+
+    /// Only match accounts whose name is exactly `name`, length prefix
+    /// included.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Only match accounts whose name starts with `prefix`. The comparison is
+    /// done on raw bytes and is case sensitive.
+    pub fn name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.name_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Only match accounts whose data is exactly `size` bytes long.
+    pub fn data_size(mut self, size: u64) -> Self {
+        self.data_size = Some(size);
+        self
+    }
+
+    pub fn filters(&self) -> Vec<RpcFilterType> {
+        let mut filters = Vec::new();
+
+        if let Some(size) = self.data_size {
+            filters.push(RpcFilterType::DataSize(size));
+        }
+
+        if let Some(variant) = self.variant {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                VARIANT_OFFSET,
+                &[variant],
+            )));
+        }
+
+        if let Some(name) = &self.name {
+            let mut bytes = (name.len() as u32).to_le_bytes().to_vec();
+            bytes.extend_from_slice(name.as_bytes());
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                NAME_LEN_OFFSET,
+                &bytes,
+            )));
+        }
+
+        if let Some(prefix) =
+            self.name_prefix.as_ref().filter(|p| !p.is_empty())
+        {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                NAME_OFFSET,
+                prefix.as_bytes(),
+            )));
+        }
+
+        filters
+    }
+
+    /// Same as [`filters`](Self::filters), but `None` when nothing is
+    /// filtered, as expected by `RpcProgramAccountsConfig`.
+    pub fn rpc_filters(&self) -> Option<Vec<RpcFilterType>> {
+        let filters = self.filters();
+        (!filters.is_empty()).then_some(filters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intro::StudentIntro;
+    use borsh::BorshSerialize;
+
+    fn intro_data(variant: u8, name: &str) -> Vec<u8> {
+        StudentIntro {
+            variant,
+            name: name.to_string(),
+            message: "hello".to_string(),
+        }
+        .try_to_vec()
+        .unwrap()
+    }
+
+    fn matches(query: &IntroQuery, data: &[u8]) -> bool {
+        query.filters().iter().all(|filter| match filter {
+            RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+            RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+            _ => unreachable!(),
+        })
+    }
+
+    #[test]
+    fn no_filters_by_default() {
+        assert!(IntroQuery::new().rpc_filters().is_none());
+        assert!(IntroQuery::new().name_prefix("").rpc_filters().is_none());
+    }
+
+    #[test]
+    fn filters_by_variant() {
+        let query = IntroQuery::new().variant(0);
+        assert!(matches(&query, &intro_data(0, "alice")));
+        assert!(!matches(&query, &intro_data(1, "alice")));
+    }
+
+    #[test]
+    fn filters_by_exact_name() {
+        let query = IntroQuery::new().name("ali");
+        assert!(matches(&query, &intro_data(0, "ali")));
+        assert!(!matches(&query, &intro_data(0, "alice")));
+        assert!(!matches(&query, &intro_data(0, "bob")));
+    }
+
+    #[test]
+    fn filters_by_name_prefix() {
+        let query = IntroQuery::new().variant(0).name_prefix("ali");
+        assert!(matches(&query, &intro_data(0, "alice")));
+        assert!(matches(&query, &intro_data(0, "ali")));
+        assert!(!matches(&query, &intro_data(0, "Alice")));
+        assert!(!matches(&query, &intro_data(1, "alice")));
+    }
+
+    #[test]
+    fn filters_by_data_size() {
+        let data = intro_data(0, "alice");
+        assert!(matches(
+            &IntroQuery::new().data_size(data.len() as u64),
+            &data
+        ));
+        assert!(!matches(&IntroQuery::new().data_size(1), &data));
+    }
+}
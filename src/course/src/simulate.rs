@@ -0,0 +1,183 @@
+use crate::budget::{compile_message, writable_accounts};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_client::RpcClient,
+    rpc_config::{
+        RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+    },
+};
+use solana_sdk::{
+    account::Account,
+    address_lookup_table_account::AddressLookupTableAccount,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signer::signers::Signers,
+    transaction::{TransactionError, VersionedTransaction},
+};
+
+#[derive(Debug)]
+pub struct Simulation {
+    /// Error the transaction would fail with, `None` if it would succeed.
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    /// State of every writable account before and after the transaction.
+    pub changes: Vec<AccountChange>,
+}
+
+#[derive(Debug)]
+pub struct AccountChange {
+    pub pubkey: Pubkey,
+    /// `None` if the account does not exist yet.
+    pub before: Option<Account>,
+    /// `None` if the account does not exist afterwards, or the simulation
+    /// failed.
+    pub after: Option<Account>,
+}
+
+impl AccountChange {
+    pub fn is_changed(&self) -> bool {
+        self.before != self.after
+    }
+
+    /// Difference in lamports, positive when the account gains lamports.
+    pub fn lamports_delta(&self) -> i128 {
+        let lamports = |account: &Option<Account>| {
+            account.as_ref().map_or(0, |account| i128::from(account.lamports))
+        };
+        lamports(&self.after) - lamports(&self.before)
+    }
+
+    /// Number of data bytes that differ, counting bytes past the end of the
+    /// shorter side as changed.
+    pub fn data_bytes_changed(&self) -> usize {
+        let (before, after) = (data(&self.before), data(&self.after));
+        let common = before.iter().zip(after).filter(|(a, b)| a != b).count();
+        common + before.len().abs_diff(after.len())
+    }
+}
+
+/// Signs `instructions` and simulates them instead of sending, returning the
+/// program logs, compute units consumed and the writable accounts before and
+/// after. Fails if `signers` are not exactly the signers the instructions
+/// need.
+pub fn simulate<T: Signers + ?Sized>(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &T,
+) -> ClientResult<Simulation> {
+    simulate_versioned(client, instructions, payer, signers, &[])
+}
+
+/// Like [`simulate`], simulating a v0 transaction that resolves accounts
+/// through `lookup_tables`, or a legacy one when there are none.
+pub fn simulate_versioned<T: Signers + ?Sized>(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &T,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> ClientResult<Simulation> {
+    let latest_blockhash = client.get_latest_blockhash()?;
+    let message =
+        compile_message(instructions, payer, lookup_tables, latest_blockhash)?;
+    let transaction = VersionedTransaction::try_new(message, signers)?;
+
+    simulate_signed_transaction(client, &transaction, lookup_tables)
+}
+
+/// Simulates an already signed transaction, such as one signed offline.
+pub fn simulate_signed_transaction(
+    client: &RpcClient,
+    transaction: &VersionedTransaction,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> ClientResult<Simulation> {
+    let writable = writable_accounts(&transaction.message, lookup_tables);
+    let before = client.get_multiple_accounts(&writable)?;
+
+    let result = client.simulate_transaction_with_config(
+        transaction,
+        RpcSimulateTransactionConfig {
+            sig_verify: true,
+            commitment: Some(client.commitment()),
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: writable.iter().map(Pubkey::to_string).collect(),
+            }),
+            ..RpcSimulateTransactionConfig::default()
+        },
+    )?;
+
+    let after = result.value.accounts.unwrap_or_default();
+    let changes = writable
+        .into_iter()
+        .zip(before)
+        .enumerate()
+        .map(|(i, (pubkey, before))| AccountChange {
+            pubkey,
+            before,
+            after: after
+                .get(i)
+                .and_then(Option::as_ref)
+                .and_then(|account| account.decode()),
+        })
+        .collect();
+
+    Ok(Simulation {
+        err: result.value.err,
+        logs: result.value.logs.unwrap_or_default(),
+        units_consumed: result.value.units_consumed,
+        changes,
+    })
+}
+
+fn data(account: &Option<Account>) -> &[u8] {
+    account.as_ref().map_or(&[], |account| &account.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{signature::Keypair, signer::Signer, system_instruction};
+
+    #[test]
+    fn wrong_signers_are_an_error() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let payer = Keypair::new();
+        let other = Keypair::new();
+        let instruction = system_instruction::transfer(
+            &payer.pubkey(),
+            &Pubkey::new_unique(),
+            1,
+        );
+
+        let result =
+            simulate(&client, &[instruction], &payer.pubkey(), &[&other]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn compares_accounts() {
+        let before = Account {
+            lamports: 10,
+            data: vec![1, 2, 3],
+            ..Account::default()
+        };
+        let change = AccountChange {
+            pubkey: Pubkey::new_unique(),
+            before: Some(before.clone()),
+            after: Some(Account {
+                lamports: 4,
+                data: vec![1, 9],
+                ..before
+            }),
+        };
+
+        assert!(change.is_changed());
+        assert_eq!(change.lamports_delta(), -6);
+        assert_eq!(change.data_bytes_changed(), 2);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::budget::{
+    compile_message, estimate_compute_budget, ComputeBudgetConfig,
+};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_client::RpcClient,
+    rpc_config::RpcSendTransactionConfig,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    message::VersionedMessage,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::signers::Signers,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SubmitOutcome {
+    Confirmed(Signature),
+    /// The transaction landed but an instruction failed.
+    Failed {
+        signature: Signature,
+        err: TransactionError,
+    },
+    /// The blockhash of the last signed attempt expired before it landed, so
+    /// it never will.
+    Expired(Signature),
+}
+
+impl SubmitOutcome {
+    /// Signature of the last attempt.
+    pub fn signature(&self) -> Signature {
+        match self {
+            SubmitOutcome::Confirmed(signature)
+            | SubmitOutcome::Failed {
+                signature,
+                ..
+            }
+            | SubmitOutcome::Expired(signature) => *signature,
+        }
+    }
+
+    /// The signature of a confirmed transaction, failures and expiry as
+    /// errors.
+    pub fn into_result(self) -> ClientResult<Signature> {
+        match self {
+            SubmitOutcome::Confirmed(signature) => Ok(signature),
+            SubmitOutcome::Failed {
+                err,
+                ..
+            } => Err(err.into()),
+            SubmitOutcome::Expired(signature) => Err(ClientErrorKind::Custom(
+                format!("transaction {signature} expired before it landed"),
+            )
+            .into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SubmitConfig {
+    /// How often the transaction is sent again while it has not landed.
+    pub rebroadcast_interval: Duration,
+    pub poll_interval: Duration,
+    /// Times the transaction is signed again with a fresh blockhash after the
+    /// previous one expired, 0 never re-signs.
+    pub max_resigns: u32,
+}
+
+impl Default for SubmitConfig {
+    fn default() -> Self {
+        Self {
+            rebroadcast_interval: Duration::from_secs(2),
+            poll_interval: Duration::from_millis(500),
+            max_resigns: 2,
+        }
+    }
+}
+
+/// Signs `message` with a recent blockhash and sends it, rebroadcasting until
+/// it lands or the blockhash expires.
+///
+/// Errors are only returned when the cluster cannot be reached; transactions
+/// that fail or expire are reported through the outcome.
+pub fn submit_transaction<T: Signers + ?Sized>(
+    client: &RpcClient,
+    mut message: VersionedMessage,
+    signers: &T,
+    config: &SubmitConfig,
+) -> ClientResult<SubmitOutcome> {
+    let mut resigns = 0;
+
+    loop {
+        let (blockhash, last_valid_block_height) =
+            client.get_latest_blockhash_with_commitment(client.commitment())?;
+        message.set_recent_blockhash(blockhash);
+        let transaction =
+            VersionedTransaction::try_new(message.clone(), signers)?;
+
+        let outcome = send_until_expired(
+            client,
+            &transaction,
+            last_valid_block_height,
+            config,
+        )?;
+        if !should_resign(&outcome, resigns, config) {
+            return Ok(outcome);
+        }
+        resigns += 1;
+    }
+}
+
+/// Whether an attempt is signed again with a fresh blockhash, only when it
+/// expired and `resigns` earlier attempts leave room for another.
+fn should_resign(
+    outcome: &SubmitOutcome,
+    resigns: u32,
+    config: &SubmitConfig,
+) -> bool {
+    matches!(outcome, SubmitOutcome::Expired(_)) && resigns < config.max_resigns
+}
+
+/// Adds a compute budget to `instructions` and submits them with
+/// [`submit_transaction`].
+pub fn submit_with_compute_budget<T: Signers + ?Sized>(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &T,
+    budget: &ComputeBudgetConfig,
+    config: &SubmitConfig,
+) -> ClientResult<SubmitOutcome> {
+    let compute_budget =
+        estimate_compute_budget(client, instructions, payer, budget)?;
+    let instructions =
+        [compute_budget.instructions(), instructions.to_vec()].concat();
+    let message = compile_message(&instructions, payer, &[], Hash::default())?;
+
+    submit_transaction(client, message, signers, config)
+}
+
+/// The outcome of a transaction its first send failed for, `None` when the
+/// error is not about the transaction itself.
+fn send_error_outcome(
+    signature: Signature,
+    e: &ClientError,
+) -> Option<SubmitOutcome> {
+    match e.get_transaction_error()? {
+        // The node has not seen the blockhash or already dropped it, either
+        // way signing again with a fresh one may still land.
+        TransactionError::BlockhashNotFound => {
+            Some(SubmitOutcome::Expired(signature))
+        }
+        err => Some(SubmitOutcome::Failed {
+            signature,
+            err,
+        }),
+    }
+}
+
+fn send_until_expired(
+    client: &RpcClient,
+    transaction: &VersionedTransaction,
+    last_valid_block_height: u64,
+    config: &SubmitConfig,
+) -> ClientResult<SubmitOutcome> {
+    let signature = transaction.signatures[0];
+
+    // The first send runs preflight so program errors surface right away.
+    if let Err(e) = client.send_transaction(transaction) {
+        return send_error_outcome(signature, &e).ok_or(e);
+    }
+    let mut last_sent = Instant::now();
+
+    loop {
+        sleep(config.poll_interval);
+
+        // Read the block height first, a transaction seen as missing after
+        // the blockhash expired can no longer land.
+        let block_height =
+            client.get_block_height_with_commitment(client.commitment())?;
+        match client.get_signature_status_with_commitment(
+            &signature,
+            client.commitment(),
+        )? {
+            Some(Ok(())) => return Ok(SubmitOutcome::Confirmed(signature)),
+            Some(Err(err)) => {
+                return Ok(SubmitOutcome::Failed {
+                    signature,
+                    err,
+                })
+            }
+            // A transaction only processed so far may still confirm, signing
+            // it again then would send it twice.
+            None if block_height > last_valid_block_height
+                && client
+                    .get_signature_status_with_commitment(
+                        &signature,
+                        CommitmentConfig::processed(),
+                    )?
+                    .is_none() =>
+            {
+                return Ok(SubmitOutcome::Expired(signature))
+            }
+            None => {}
+        }
+
+        if last_sent.elapsed() >= config.rebroadcast_interval {
+            // Rebroadcast failures are not fatal, the next poll decides.
+            let _ = client.send_transaction_with_config(
+                transaction,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    max_retries: Some(0),
+                    ..RpcSendTransactionConfig::default()
+                },
+            );
+            last_sent = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_client::{
+        rpc_request::{RpcError, RpcRequest, RpcResponseErrorData},
+        rpc_response::RpcSimulateTransactionResult,
+    };
+    use solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        system_instruction,
+    };
+    use std::collections::HashMap;
+
+    fn config(max_resigns: u32) -> SubmitConfig {
+        SubmitConfig {
+            rebroadcast_interval: Duration::ZERO,
+            poll_interval: Duration::ZERO,
+            max_resigns,
+        }
+    }
+
+    fn submit(client: &RpcClient) -> SubmitOutcome {
+        let payer = Keypair::new();
+        let instruction = system_instruction::transfer(
+            &payer.pubkey(),
+            &Pubkey::new_unique(),
+            1,
+        );
+        let message = compile_message(
+            &[instruction],
+            &payer.pubkey(),
+            &[],
+            Hash::default(),
+        )
+        .unwrap();
+
+        submit_transaction(client, message, &[&payer], &config(0)).unwrap()
+    }
+
+    fn preflight_failure(err: TransactionError) -> ClientError {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code: -32002,
+            message: "Transaction simulation failed".to_string(),
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(
+                RpcSimulateTransactionResult {
+                    err: Some(err),
+                    logs: None,
+                    accounts: None,
+                    units_consumed: None,
+                    return_data: None,
+                },
+            ),
+        })
+        .into()
+    }
+
+    #[test]
+    fn confirms_landed_transactions() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let outcome = submit(&client);
+
+        assert!(matches!(outcome, SubmitOutcome::Confirmed(_)));
+        assert_eq!(outcome.clone().into_result().unwrap(), outcome.signature());
+    }
+
+    #[test]
+    fn reports_failed_transactions() {
+        let client = RpcClient::new_mock("instruction_error".to_string());
+
+        match submit(&client) {
+            SubmitOutcome::Failed {
+                err,
+                ..
+            } => assert_eq!(
+                err,
+                TransactionError::InstructionError(
+                    0,
+                    InstructionError::UninitializedAccount
+                )
+            ),
+            outcome => panic!("unexpected outcome {outcome:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_transactions_expire_past_the_last_valid_block() {
+        // The mock blockhash is valid up to block 1234.
+        let client = RpcClient::new_mock_with_mocks(
+            "sig_not_found".to_string(),
+            HashMap::from([(RpcRequest::GetBlockHeight, json!(1235))]),
+        );
+        let outcome = submit(&client);
+
+        assert_eq!(outcome, SubmitOutcome::Expired(outcome.signature()));
+        let err = outcome.into_result().unwrap_err();
+        assert!(err.to_string().contains("expired before it landed"));
+    }
+
+    #[test]
+    fn blockhash_not_found_is_expired() {
+        let signature = Signature::new_unique();
+
+        assert_eq!(
+            send_error_outcome(
+                signature,
+                &preflight_failure(TransactionError::BlockhashNotFound)
+            ),
+            Some(SubmitOutcome::Expired(signature))
+        );
+        assert_eq!(
+            send_error_outcome(
+                signature,
+                &TransactionError::BlockhashNotFound.into()
+            ),
+            Some(SubmitOutcome::Expired(signature))
+        );
+        assert_eq!(
+            send_error_outcome(
+                signature,
+                &preflight_failure(TransactionError::AccountNotFound)
+            ),
+            Some(SubmitOutcome::Failed {
+                signature,
+                err: TransactionError::AccountNotFound,
+            })
+        );
+        let unreachable = ClientErrorKind::Custom("no route".to_string());
+        assert_eq!(send_error_outcome(signature, &unreachable.into()), None);
+    }
+
+    #[test]
+    fn resigns_only_expired_attempts_up_to_the_limit() {
+        let signature = Signature::new_unique();
+        let expired = SubmitOutcome::Expired(signature);
+
+        assert!(should_resign(&expired, 0, &config(2)));
+        assert!(should_resign(&expired, 1, &config(2)));
+        assert!(!should_resign(&expired, 2, &config(2)));
+        assert!(!should_resign(&expired, 0, &config(0)));
+        assert!(!should_resign(
+            &SubmitOutcome::Confirmed(signature),
+            0,
+            &config(2)
+        ));
+        assert!(!should_resign(
+            &SubmitOutcome::Failed {
+                signature,
+                err: TransactionError::AccountNotFound,
+            },
+            0,
+            &config(2)
+        ));
+    }
+}
@@ -0,0 +1,839 @@
+use crate::{
+    budget::ComputeBudgetConfig,
+    keypair::unique_signers,
+    submit::{submit_with_compute_budget, SubmitConfig},
+    token_2022::extensions,
+};
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_sdk::{
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{keypair::Keypair, Signer},
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{
+            instruction::transfer_checked_with_fee, TransferFeeConfig,
+        },
+        BaseState, ExtensionType, StateWithExtensions,
+    },
+    instruction,
+    pod::pod_from_bytes,
+    state::{Account as TokenAccount, Mint},
+};
+use thiserror::Error;
+
+/// How the token helpers that send transactions price and submit them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SendConfig {
+    pub budget: ComputeBudgetConfig,
+    pub submit: SubmitConfig,
+}
+
+#[derive(Debug, Error)]
+pub enum TokenError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    #[error("token program error: {0}")]
+    Program(#[from] ProgramError),
+
+    #[error("{0} is not owned by the token program or Token-2022")]
+    NotToken(Pubkey),
+
+    #[error("invalid token extension: {0}")]
+    InvalidExtension(String),
+}
+
+/// Reads a mint of either token program, without its extensions.
+pub fn get_mint(client: &RpcClient, mint: &Pubkey) -> Result<Mint, TokenError> {
+    Ok(get_state(client, mint)?.1)
+}
+
+/// Reads a token account of either token program, without its extensions.
+pub fn get_token_account(
+    client: &RpcClient,
+    account: &Pubkey,
+) -> Result<TokenAccount, TokenError> {
+    Ok(get_state(client, account)?.1)
+}
+
+/// The program owning a mint or token account, spl-token or Token-2022.
+pub fn token_program(
+    client: &RpcClient,
+    address: &Pubkey,
+) -> Result<Pubkey, TokenError> {
+    let owner = client.get_account(address)?.owner;
+    if !is_token_program(&owner) {
+        return Err(TokenError::NotToken(*address));
+    }
+    Ok(owner)
+}
+
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_token::id() || *program_id == spl_token_2022::id()
+}
+
+/// Address of the token account holding `wallet`'s tokens of `mint`, which
+/// depends on the program owning the mint.
+pub fn associated_token_address(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    get_associated_token_address_with_program_id(wallet, mint, token_program)
+}
+
+/// Returns `wallet`'s associated token account for `mint`, creating it first
+/// if it does not exist yet.
+pub fn get_or_create_associated_token_account(
+    client: &RpcClient,
+    payer: &Keypair,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    config: &SendConfig,
+) -> Result<Pubkey, TokenError> {
+    let program = token_program(client, mint)?;
+    let address = associated_token_address(wallet, mint, &program);
+    let exists = client
+        .get_account_with_commitment(&address, client.commitment())?
+        .value
+        .is_some();
+
+    if !exists {
+        let instruction = create_associated_token_account_instruction(
+            client,
+            &payer.pubkey(),
+            wallet,
+            mint,
+        )?;
+        send(client, &[instruction], &[payer], config)?;
+    }
+    Ok(address)
+}
+
+/// Creates `wallet`'s associated token account for `mint`, doing nothing if
+/// it already exists.
+pub fn create_associated_token_account_instruction(
+    client: &RpcClient,
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, TokenError> {
+    let program = token_program(client, mint)?;
+    Ok(create_associated_token_account_idempotent(
+        payer, wallet, mint, &program,
+    ))
+}
+
+/// Creates and initializes a mint of the token program whose supply is
+/// controlled by `mint_authority`. See [`crate::token_2022::create_mint`] for
+/// mints with extensions.
+pub fn create_mint(
+    client: &RpcClient,
+    payer: &Keypair,
+    mint: &Keypair,
+    mint_authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instructions = create_mint_instructions(
+        client,
+        &payer.pubkey(),
+        &mint.pubkey(),
+        mint_authority,
+        freeze_authority,
+        decimals,
+    )?;
+
+    send(client, &instructions, &[payer, mint], config)
+}
+
+/// Instructions of [`create_mint`], signed by `payer` and `mint`.
+pub fn create_mint_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+) -> Result<Vec<Instruction>, TokenError> {
+    let rent = client.get_minimum_balance_for_rent_exemption(Mint::LEN)?;
+    Ok(vec![
+        system_instruction::create_account(
+            payer,
+            mint,
+            rent,
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        instruction::initialize_mint(
+            &spl_token::id(),
+            mint,
+            mint_authority,
+            freeze_authority,
+            decimals,
+        )?,
+    ])
+}
+
+/// Creates an auxiliary token account for `mint` at the address of
+/// `account`, holding tokens for `owner`. Most wallets only need their
+/// associated token account, see [`get_or_create_associated_token_account`].
+///
+/// The account is sized for the extensions the mint requires on its
+/// accounts, such as the withheld amount of a mint with transfer fees.
+pub fn create_token_account(
+    client: &RpcClient,
+    payer: &Keypair,
+    account: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instructions = create_token_account_instructions(
+        client,
+        &payer.pubkey(),
+        &account.pubkey(),
+        mint,
+        owner,
+    )?;
+
+    send(client, &instructions, &[payer, account], config)
+}
+
+/// Instructions of [`create_token_account`], signed by `payer` and
+/// `account`.
+pub fn create_token_account_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    account: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Vec<Instruction>, TokenError> {
+    let mint_account = client.get_account(mint)?;
+    let program = mint_account.owner;
+    if !is_token_program(&program) {
+        return Err(TokenError::NotToken(*mint));
+    }
+    let mint_extensions: Vec<_> = extensions(&mint_account.data)
+        .into_iter()
+        .filter_map(|(extension, _)| ExtensionType::try_from(extension).ok())
+        .collect();
+    let space = ExtensionType::get_account_len::<TokenAccount>(
+        &ExtensionType::get_required_init_account_extensions(&mint_extensions),
+    );
+
+    let rent = client.get_minimum_balance_for_rent_exemption(space)?;
+    Ok(vec![
+        system_instruction::create_account(
+            payer,
+            account,
+            rent,
+            space as u64,
+            &program,
+        ),
+        instruction::initialize_account(&program, account, mint, owner)?,
+    ])
+}
+
+/// Mints `amount` base units of `mint` to the `destination` token account.
+pub fn mint_to(
+    client: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    mint_authority: &Keypair,
+    amount: u64,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instruction = mint_to_instruction(
+        client,
+        mint,
+        destination,
+        &mint_authority.pubkey(),
+        amount,
+    )?;
+
+    send(client, &[instruction], &[payer, mint_authority], config)
+}
+
+pub fn mint_to_instruction(
+    client: &RpcClient,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    mint_authority: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, TokenError> {
+    let program = token_program(client, mint)?;
+    Ok(instruction::mint_to(
+        &program,
+        mint,
+        destination,
+        mint_authority,
+        &[],
+        amount,
+    )?)
+}
+
+/// Mints `amount` base units of `mint` to `wallet`'s associated token
+/// account, creating the account in the same transaction if needed.
+pub fn mint_to_wallet(
+    client: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    wallet: &Pubkey,
+    mint_authority: &Keypair,
+    amount: u64,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instructions = mint_to_wallet_instructions(
+        client,
+        &payer.pubkey(),
+        mint,
+        wallet,
+        &mint_authority.pubkey(),
+        amount,
+    )?;
+
+    send(client, &instructions, &[payer, mint_authority], config)
+}
+
+pub fn mint_to_wallet_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    wallet: &Pubkey,
+    mint_authority: &Pubkey,
+    amount: u64,
+) -> Result<Vec<Instruction>, TokenError> {
+    let program = token_program(client, mint)?;
+    Ok(vec![
+        create_associated_token_account_idempotent(
+            payer, wallet, mint, &program,
+        ),
+        instruction::mint_to(
+            &program,
+            mint,
+            &associated_token_address(wallet, mint, &program),
+            mint_authority,
+            &[],
+            amount,
+        )?,
+    ])
+}
+
+/// Moves `amount` base units between two token accounts of the same mint.
+/// The mint and its decimals are read from `source`. When the mint charges
+/// a transfer fee, the fee is withheld from the amount in `destination`.
+pub fn transfer_checked(
+    client: &RpcClient,
+    payer: &Keypair,
+    source: &Pubkey,
+    destination: &Pubkey,
+    owner: &Keypair,
+    amount: u64,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instruction = transfer_checked_instruction(
+        client,
+        source,
+        destination,
+        &owner.pubkey(),
+        amount,
+    )?;
+
+    send(client, &[instruction], &[payer, owner], config)
+}
+
+pub fn transfer_checked_instruction(
+    client: &RpcClient,
+    source: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, TokenError> {
+    let (program, account) = get_state::<TokenAccount>(client, source)?;
+    transfer_instruction(
+        client,
+        &program,
+        source,
+        &account.mint,
+        destination,
+        owner,
+        amount,
+    )
+}
+
+/// Moves `amount` base units from `source` to `wallet`'s associated token
+/// account, creating the account in the same transaction if needed.
+pub fn transfer_to_wallet(
+    client: &RpcClient,
+    payer: &Keypair,
+    source: &Pubkey,
+    wallet: &Pubkey,
+    owner: &Keypair,
+    amount: u64,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instructions = transfer_to_wallet_instructions(
+        client,
+        &payer.pubkey(),
+        source,
+        wallet,
+        &owner.pubkey(),
+        amount,
+    )?;
+
+    send(client, &instructions, &[payer, owner], config)
+}
+
+pub fn transfer_to_wallet_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    source: &Pubkey,
+    wallet: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Result<Vec<Instruction>, TokenError> {
+    let (program, account) = get_state::<TokenAccount>(client, source)?;
+    let mint = account.mint;
+    Ok(vec![
+        create_associated_token_account_idempotent(
+            payer, wallet, &mint, &program,
+        ),
+        transfer_instruction(
+            client,
+            &program,
+            source,
+            &mint,
+            &associated_token_address(wallet, &mint, &program),
+            owner,
+            amount,
+        )?,
+    ])
+}
+
+/// Destroys `amount` base units held by `account`, lowering the supply.
+pub fn burn_checked(
+    client: &RpcClient,
+    payer: &Keypair,
+    account: &Pubkey,
+    owner: &Keypair,
+    amount: u64,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instruction =
+        burn_checked_instruction(client, account, &owner.pubkey(), amount)?;
+
+    send(client, &[instruction], &[payer, owner], config)
+}
+
+pub fn burn_checked_instruction(
+    client: &RpcClient,
+    account: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, TokenError> {
+    let (program, token_account) = get_state::<TokenAccount>(client, account)?;
+    let mint = token_account.mint;
+    let decimals = get_mint(client, &mint)?.decimals;
+    Ok(instruction::burn_checked(
+        &program,
+        account,
+        &mint,
+        owner,
+        &[],
+        amount,
+        decimals,
+    )?)
+}
+
+/// Lets `delegate` transfer or burn up to `amount` base units from
+/// `account`, replacing any previous delegation.
+pub fn approve(
+    client: &RpcClient,
+    payer: &Keypair,
+    account: &Pubkey,
+    delegate: &Pubkey,
+    owner: &Keypair,
+    amount: u64,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instruction = approve_instruction(
+        client,
+        account,
+        delegate,
+        &owner.pubkey(),
+        amount,
+    )?;
+
+    send(client, &[instruction], &[payer, owner], config)
+}
+
+pub fn approve_instruction(
+    client: &RpcClient,
+    account: &Pubkey,
+    delegate: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, TokenError> {
+    let program = token_program(client, account)?;
+    Ok(instruction::approve(&program, account, delegate, owner, &[], amount)?)
+}
+
+/// Removes the delegation of `account`.
+pub fn revoke(
+    client: &RpcClient,
+    payer: &Keypair,
+    account: &Pubkey,
+    owner: &Keypair,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instruction = revoke_instruction(client, account, &owner.pubkey())?;
+
+    send(client, &[instruction], &[payer, owner], config)
+}
+
+pub fn revoke_instruction(
+    client: &RpcClient,
+    account: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Instruction, TokenError> {
+    let program = token_program(client, account)?;
+    Ok(instruction::revoke(&program, account, owner, &[])?)
+}
+
+/// Closes an empty token account, or a wrapped SOL account, sending its
+/// lamports to `destination`.
+pub fn close_account(
+    client: &RpcClient,
+    payer: &Keypair,
+    account: &Pubkey,
+    destination: &Pubkey,
+    owner: &Keypair,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instruction = close_account_instruction(
+        client,
+        account,
+        destination,
+        &owner.pubkey(),
+    )?;
+
+    send(client, &[instruction], &[payer, owner], config)
+}
+
+pub fn close_account_instruction(
+    client: &RpcClient,
+    account: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Instruction, TokenError> {
+    let program = token_program(client, account)?;
+    Ok(instruction::close_account(&program, account, destination, owner, &[])?)
+}
+
+/// Updates the token balance of a wrapped SOL account to match the lamports
+/// sent to it.
+pub fn sync_native(
+    client: &RpcClient,
+    payer: &Keypair,
+    account: &Pubkey,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instruction = sync_native_instruction(client, account)?;
+
+    send(client, &[instruction], &[payer], config)
+}
+
+pub fn sync_native_instruction(
+    client: &RpcClient,
+    account: &Pubkey,
+) -> Result<Instruction, TokenError> {
+    let program = token_program(client, account)?;
+    Ok(instruction::sync_native(&program, account)?)
+}
+
+/// A checked transfer of `amount`, passing the fee the mint expects for the
+/// current epoch when it charges transfer fees.
+fn transfer_instruction(
+    client: &RpcClient,
+    program: &Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, TokenError> {
+    let data = client.get_account_data(mint)?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+    let decimals = state.base.decimals;
+
+    let fee_config =
+        extensions(&data).into_iter().find_map(|(extension, value)| {
+            (extension == u16::from(ExtensionType::TransferFeeConfig))
+                .then(|| pod_from_bytes::<TransferFeeConfig>(value).ok())
+                .flatten()
+        });
+    let Some(fee_config) = fee_config else {
+        return Ok(instruction::transfer_checked(
+            program,
+            source,
+            mint,
+            destination,
+            owner,
+            &[],
+            amount,
+            decimals,
+        )?);
+    };
+    let epoch = client.get_epoch_info()?.epoch;
+    let fee = fee_config.calculate_epoch_fee(epoch, amount).ok_or(
+        ProgramError::from(spl_token_2022::error::TokenError::Overflow),
+    )?;
+
+    Ok(transfer_checked_with_fee(
+        program,
+        source,
+        mint,
+        destination,
+        owner,
+        &[],
+        amount,
+        decimals,
+        fee,
+    )?)
+}
+
+/// Reads a mint or token account and the program owning it.
+pub(crate) fn get_state<S: BaseState>(
+    client: &RpcClient,
+    address: &Pubkey,
+) -> Result<(Pubkey, S), TokenError> {
+    let account = client.get_account(address)?;
+    if !is_token_program(&account.owner) {
+        return Err(TokenError::NotToken(*address));
+    }
+    let state = StateWithExtensions::<S>::unpack(&account.data)?.base;
+    Ok((account.owner, state))
+}
+
+/// Submits `instructions` with `signers`, the first of which pays.
+pub(crate) fn send(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let signature = submit_with_compute_budget(
+        client,
+        instructions,
+        &signers[0].pubkey(),
+        &unique_signers(signers),
+        &config.budget,
+        &config.submit,
+    )?
+    .into_result()?;
+
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_account_decoder::{UiAccount, UiAccountEncoding};
+    use solana_client::rpc_request::RpcRequest;
+    use solana_sdk::account::Account;
+    use spl_token_2022::extension::{
+        transfer_fee::TransferFee, StateWithExtensionsMut,
+    };
+    use std::collections::HashMap;
+
+    /// A Token-2022 mint with 2 decimals, charging `fee` if given.
+    fn mint_data(fee: Option<(u16, u64)>) -> Vec<u8> {
+        let extensions: &[ExtensionType] = match fee {
+            Some(_) => &[ExtensionType::TransferFeeConfig],
+            None => &[],
+        };
+        let mut data =
+            vec![0; ExtensionType::get_account_len::<Mint>(extensions)];
+        let mut state =
+            StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data)
+                .unwrap();
+        if let Some((basis_points, maximum_fee)) = fee {
+            let config =
+                state.init_extension::<TransferFeeConfig>(true).unwrap();
+            let fee = TransferFee {
+                epoch: 0.into(),
+                maximum_fee: maximum_fee.into(),
+                transfer_fee_basis_points: basis_points.into(),
+            };
+            config.older_transfer_fee = fee;
+            config.newer_transfer_fee = fee;
+        }
+        state.base.decimals = 2;
+        state.base.is_initialized = true;
+        state.pack_base();
+        if fee.is_some() {
+            state.init_account_type().unwrap();
+        }
+        data
+    }
+
+    /// A client that serves `data` as the Token-2022 `mint`.
+    fn client_with_mint(mint: &Pubkey, data: Vec<u8>) -> RpcClient {
+        let account = Account {
+            lamports: 1_000_000,
+            data,
+            owner: spl_token_2022::id(),
+            ..Account::default()
+        };
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            json!({
+                "context": { "slot": 1 },
+                "value": UiAccount::encode(
+                    mint,
+                    &account,
+                    UiAccountEncoding::Base64,
+                    None,
+                    None,
+                ),
+            }),
+        );
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+    }
+
+    fn transfer(
+        client: &RpcClient,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> (Result<Instruction, TokenError>, [Pubkey; 3]) {
+        let accounts =
+            [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let [source, destination, owner] = &accounts;
+        let instruction = transfer_instruction(
+            client,
+            &spl_token_2022::id(),
+            source,
+            mint,
+            destination,
+            owner,
+            amount,
+        );
+        (instruction, accounts)
+    }
+
+    #[test]
+    fn transfers_without_fee_config_are_plain() {
+        let mint = Pubkey::new_unique();
+        let client = client_with_mint(&mint, mint_data(None));
+
+        let (instruction, [source, destination, owner]) =
+            transfer(&client, &mint, 1_000);
+
+        let expected = instruction::transfer_checked(
+            &spl_token_2022::id(),
+            &source,
+            &mint,
+            &destination,
+            &owner,
+            &[],
+            1_000,
+            2,
+        )
+        .unwrap();
+        assert_eq!(instruction.unwrap(), expected);
+    }
+
+    #[test]
+    fn transfers_with_fee_config_pay_the_fee() {
+        // 1% rounded up, capped at 7 base units.
+        for (amount, fee) in [(500, 5), (501, 6), (10_000, 7)] {
+            let mint = Pubkey::new_unique();
+            let client = client_with_mint(&mint, mint_data(Some((100, 7))));
+
+            let (instruction, [source, destination, owner]) =
+                transfer(&client, &mint, amount);
+
+            let expected = transfer_checked_with_fee(
+                &spl_token_2022::id(),
+                &source,
+                &mint,
+                &destination,
+                &owner,
+                &[],
+                amount,
+                2,
+                fee,
+            )
+            .unwrap();
+            assert_eq!(instruction.unwrap(), expected, "{amount}");
+        }
+    }
+
+    #[test]
+    fn fee_overflow_is_an_error() {
+        let mint = Pubkey::new_unique();
+        // More than 100%, so the fee of the largest amount exceeds a u64.
+        let client =
+            client_with_mint(&mint, mint_data(Some((u16::MAX, u64::MAX))));
+
+        let (instruction, _) = transfer(&client, &mint, u64::MAX);
+
+        assert!(matches!(
+            instruction,
+            Err(TokenError::Program(e))
+                if e == spl_token_2022::error::TokenError::Overflow.into()
+        ));
+    }
+
+    #[test]
+    fn token_accounts_have_room_for_required_extensions() {
+        for (fee, space) in [
+            (None, TokenAccount::LEN),
+            (
+                Some((100, 7)),
+                ExtensionType::get_account_len::<TokenAccount>(&[
+                    ExtensionType::TransferFeeAmount,
+                ]),
+            ),
+        ] {
+            let mint = Pubkey::new_unique();
+            let client = client_with_mint(&mint, mint_data(fee));
+            let payer = Pubkey::new_unique();
+            let account = Pubkey::new_unique();
+
+            let instructions = create_token_account_instructions(
+                &client,
+                &payer,
+                &account,
+                &mint,
+                &Pubkey::new_unique(),
+            )
+            .unwrap();
+
+            // The mock's rent exemption minimum is 20 lamports.
+            let expected = system_instruction::create_account(
+                &payer,
+                &account,
+                20,
+                space as u64,
+                &spl_token_2022::id(),
+            );
+            assert_eq!(instructions[0], expected);
+        }
+    }
+}
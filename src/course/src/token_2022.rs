@@ -0,0 +1,527 @@
+use crate::{
+    token::{send, SendConfig, TokenError},
+    token_metadata::{self, MintMetadata, METADATA_POINTER_LEN},
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{keypair::Keypair, Signer},
+    system_instruction,
+};
+use spl_token_2022::{
+    extension::{
+        default_account_state::instruction::initialize_default_account_state,
+        interest_bearing_mint,
+        transfer_fee::{
+            instruction::{
+                harvest_withheld_tokens_to_mint,
+                initialize_transfer_fee_config,
+                withdraw_withheld_tokens_from_accounts,
+                withdraw_withheld_tokens_from_mint,
+            },
+            TransferFeeAmount,
+        },
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    instruction,
+    state::{Account as TokenAccount, AccountState, Mint, Multisig},
+};
+use std::mem::size_of;
+
+/// Most source accounts a single harvest or withdraw instruction takes
+/// while staying under the transaction size limit.
+const MAX_FEE_SOURCES: usize = 20;
+
+#[derive(Clone, Copy, Debug)]
+pub struct TransferFee {
+    /// Share of every transfer withheld, in hundredths of a percent.
+    pub basis_points: u16,
+    /// Cap on the fee of a single transfer, in base units.
+    pub maximum_fee: u64,
+}
+
+/// A Token-2022 mint and the extensions it is created with. The mint
+/// authority also controls the transfer fee and the interest rate.
+#[derive(Clone, Debug)]
+pub struct MintConfig {
+    pub decimals: u8,
+    pub mint_authority: Pubkey,
+    pub freeze_authority: Option<Pubkey>,
+    pub transfer_fee: Option<TransferFee>,
+    /// Allowed to close the mint once its supply is zero.
+    pub close_authority: Option<Pubkey>,
+    /// Annual interest rate in basis points, only changing how amounts are
+    /// displayed.
+    pub interest_rate: Option<i16>,
+    /// State new token accounts start in, `Frozen` requires a freeze
+    /// authority to thaw them.
+    pub default_account_state: Option<AccountState>,
+    /// Tokens can be minted and burned but never transferred.
+    pub non_transferable: bool,
+    /// Name, symbol and URI stored in the mint itself, updatable by the mint
+    /// authority.
+    pub metadata: Option<MintMetadata>,
+}
+
+impl MintConfig {
+    /// A mint without extensions.
+    pub fn new(decimals: u8, mint_authority: Pubkey) -> Self {
+        MintConfig {
+            decimals,
+            mint_authority,
+            freeze_authority: None,
+            transfer_fee: None,
+            close_authority: None,
+            interest_rate: None,
+            default_account_state: None,
+            non_transferable: false,
+            metadata: None,
+        }
+    }
+
+    pub fn extension_types(&self) -> Vec<ExtensionType> {
+        let mut types = Vec::new();
+        if self.transfer_fee.is_some() {
+            types.push(ExtensionType::TransferFeeConfig);
+        }
+        if self.close_authority.is_some() {
+            types.push(ExtensionType::MintCloseAuthority);
+        }
+        if self.interest_rate.is_some() {
+            types.push(ExtensionType::InterestBearingConfig);
+        }
+        if self.default_account_state.is_some() {
+            types.push(ExtensionType::DefaultAccountState);
+        }
+        if self.non_transferable {
+            types.push(ExtensionType::NonTransferable);
+        }
+        types
+    }
+
+    /// Size of the mint account holding the chosen extensions. The token
+    /// metadata is not included, the program grows the account for it.
+    pub fn space(&self) -> usize {
+        let types = self.extension_types();
+        if self.metadata.is_none() {
+            return ExtensionType::get_account_len::<Mint>(&types);
+        }
+
+        let header = size_of::<u16>() * 2;
+        let len = TokenAccount::LEN
+            + size_of::<u8>()
+            + types
+                .iter()
+                .map(|extension| header + extension.get_type_len())
+                .sum::<usize>()
+            + header
+            + METADATA_POINTER_LEN;
+        // Padded so the account cannot be mistaken for a multisig.
+        if len == Multisig::LEN {
+            len + size_of::<u16>()
+        } else {
+            len
+        }
+    }
+
+    /// Size the mint grows to once its metadata is stored.
+    pub fn space_with_metadata(&self) -> usize {
+        self.space() + self.metadata.as_ref().map_or(0, MintMetadata::space)
+    }
+
+    /// Instructions initializing the extensions and then the mint itself,
+    /// which the program requires in this order.
+    pub fn instructions(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<Instruction>, TokenError> {
+        let program = spl_token_2022::id();
+        let authority = self.mint_authority;
+        let mut instructions = Vec::new();
+
+        if let Some(fee) = self.transfer_fee {
+            instructions.push(initialize_transfer_fee_config(
+                &program,
+                mint,
+                Some(&authority),
+                Some(&authority),
+                fee.basis_points,
+                fee.maximum_fee,
+            )?);
+        }
+        if let Some(close_authority) = &self.close_authority {
+            instructions.push(instruction::initialize_mint_close_authority(
+                &program,
+                mint,
+                Some(close_authority),
+            )?);
+        }
+        if let Some(rate) = self.interest_rate {
+            instructions.push(interest_bearing_mint::instruction::initialize(
+                &program,
+                mint,
+                Some(authority),
+                rate,
+            )?);
+        }
+        if let Some(state) = &self.default_account_state {
+            instructions
+                .push(initialize_default_account_state(&program, mint, state)?);
+        }
+        if self.non_transferable {
+            instructions.push(instruction::initialize_non_transferable_mint(
+                &program, mint,
+            )?);
+        }
+        if self.metadata.is_some() {
+            instructions.push(token_metadata::initialize_metadata_pointer(
+                mint,
+                Some(&authority),
+                Some(mint),
+            ));
+        }
+
+        instructions.push(instruction::initialize_mint(
+            &program,
+            mint,
+            &authority,
+            self.freeze_authority.as_ref(),
+            self.decimals,
+        )?);
+
+        if let Some(metadata) = &self.metadata {
+            instructions.push(token_metadata::initialize(
+                mint,
+                &authority,
+                &authority,
+                &metadata.name,
+                &metadata.symbol,
+                &metadata.uri,
+            ));
+        }
+        Ok(instructions)
+    }
+}
+
+/// Creates a Token-2022 mint at the address of `mint` with the extensions
+/// in `config`, paying rent for the space they take. Storing metadata needs
+/// the mint authority's signature, so `payer` must then be the mint
+/// authority.
+pub fn create_mint(
+    client: &RpcClient,
+    payer: &Keypair,
+    mint: &Keypair,
+    config: &MintConfig,
+    send_config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instructions = create_mint_instructions(
+        client,
+        &payer.pubkey(),
+        &mint.pubkey(),
+        config,
+    )?;
+
+    send(client, &instructions, &[payer, mint], send_config)
+}
+
+/// Instructions of [`create_mint`], signed by `payer` and `mint`.
+pub fn create_mint_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    config: &MintConfig,
+) -> Result<Vec<Instruction>, TokenError> {
+    let space = config.space();
+    let rent = client
+        .get_minimum_balance_for_rent_exemption(config.space_with_metadata())?;
+
+    let mut instructions = vec![system_instruction::create_account(
+        payer,
+        mint,
+        rent,
+        space as u64,
+        &spl_token_2022::id(),
+    )];
+    instructions.extend(config.instructions(mint)?);
+    Ok(instructions)
+}
+
+/// Type and value of each extension of a Token-2022 mint or token account.
+/// Unlike `get_extension_types` this also walks extensions newer than the
+/// spl-token-2022 version the course is pinned to, such as token metadata.
+pub fn extensions(data: &[u8]) -> Vec<(u16, &[u8])> {
+    let mut extensions = Vec::new();
+    let Some(mut tlv) = data.get(TokenAccount::LEN + size_of::<u8>()..) else {
+        return extensions;
+    };
+
+    while tlv.len() >= 4 {
+        let extension = u16::from_le_bytes([tlv[0], tlv[1]]);
+        let len = usize::from(u16::from_le_bytes([tlv[2], tlv[3]]));
+        // Nothing is written after an uninitialized entry.
+        if extension == 0 {
+            break;
+        }
+        let Some(value) = tlv.get(4..4 + len) else {
+            break;
+        };
+        extensions.push((extension, value));
+        tlv = &tlv[4 + len..];
+    }
+    extensions
+}
+
+/// Token accounts of `mint` holding withheld transfer fees.
+pub fn accounts_with_withheld_fees(
+    client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<Vec<Pubkey>, TokenError> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            mint.as_ref(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        with_context: None,
+    };
+    let accounts = client
+        .get_program_accounts_with_config(&spl_token_2022::id(), config)?;
+
+    Ok(accounts
+        .into_iter()
+        .filter(|(_, account)| {
+            StateWithExtensions::<TokenAccount>::unpack(&account.data)
+                .ok()
+                .and_then(|state| {
+                    state
+                        .get_extension::<TransferFeeAmount>()
+                        .ok()
+                        .map(|fees| u64::from(fees.withheld_amount) > 0)
+                })
+                .unwrap_or(false)
+        })
+        .map(|(pubkey, _)| pubkey)
+        .collect())
+}
+
+/// Moves the fees withheld in `sources` to the mint, where the withdraw
+/// authority can collect them. Anyone can harvest, so only the payer signs.
+/// Sends one transaction per batch of sources.
+pub fn harvest_withheld_fees(
+    client: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    sources: &[Pubkey],
+    config: &SendConfig,
+) -> Result<Vec<Signature>, TokenError> {
+    harvest_withheld_fees_instructions(mint, sources)?
+        .into_iter()
+        .map(|instruction| send(client, &[instruction], &[payer], config))
+        .collect()
+}
+
+/// Instructions of [`harvest_withheld_fees`], each sent in its own
+/// transaction.
+pub fn harvest_withheld_fees_instructions(
+    mint: &Pubkey,
+    sources: &[Pubkey],
+) -> Result<Vec<Instruction>, TokenError> {
+    sources
+        .chunks(MAX_FEE_SOURCES)
+        .map(|chunk| {
+            let sources: Vec<_> = chunk.iter().collect();
+            Ok(harvest_withheld_tokens_to_mint(
+                &spl_token_2022::id(),
+                mint,
+                &sources,
+            )?)
+        })
+        .collect()
+}
+
+/// Sends the fees withheld in `sources` and in the mint itself to the
+/// `destination` token account. `authority` must be the mint's withdraw
+/// authority. Sends one transaction per batch of sources and a last one
+/// emptying the mint.
+pub fn withdraw_withheld_fees(
+    client: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Keypair,
+    sources: &[Pubkey],
+    config: &SendConfig,
+) -> Result<Vec<Signature>, TokenError> {
+    withdraw_withheld_fees_instructions(
+        mint,
+        destination,
+        &authority.pubkey(),
+        sources,
+    )?
+    .into_iter()
+    .map(|instruction| {
+        send(client, &[instruction], &[payer, authority], config)
+    })
+    .collect()
+}
+
+/// Instructions of [`withdraw_withheld_fees`], each sent in its own
+/// transaction.
+pub fn withdraw_withheld_fees_instructions(
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    sources: &[Pubkey],
+) -> Result<Vec<Instruction>, TokenError> {
+    let program = spl_token_2022::id();
+    let mut instructions = Vec::new();
+
+    for chunk in sources.chunks(MAX_FEE_SOURCES) {
+        let sources: Vec<_> = chunk.iter().collect();
+        instructions.push(withdraw_withheld_tokens_from_accounts(
+            &program,
+            mint,
+            destination,
+            authority,
+            &[],
+            &sources,
+        )?);
+    }
+    instructions.push(withdraw_withheld_tokens_from_mint(
+        &program,
+        mint,
+        destination,
+        authority,
+        &[],
+    )?);
+
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token_metadata::{
+        EXTENSION_HEADER_LEN, METADATA_POINTER_EXTENSION,
+    };
+    use spl_token_2022::extension::{
+        mint_close_authority::MintCloseAuthority,
+        non_transferable::NonTransferable, transfer_fee::TransferFeeConfig,
+        StateWithExtensionsMut,
+    };
+
+    fn config() -> MintConfig {
+        MintConfig {
+            transfer_fee: Some(TransferFee {
+                basis_points: 50,
+                maximum_fee: 5_000,
+            }),
+            close_authority: Some(Pubkey::new_unique()),
+            non_transferable: true,
+            ..MintConfig::new(2, Pubkey::new_unique())
+        }
+    }
+
+    fn metadata() -> MintMetadata {
+        MintMetadata {
+            name: "Solana Course Token".to_string(),
+            symbol: "COURSE".to_string(),
+            uri: String::new(),
+        }
+    }
+
+    /// A mint with the extensions of [`config`] as the program writes it.
+    fn mint_data(config: &MintConfig) -> Vec<u8> {
+        let mut data = vec![0; config.space()];
+        let mut state =
+            StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data)
+                .unwrap();
+        state.init_extension::<TransferFeeConfig>(true).unwrap();
+        state.init_extension::<MintCloseAuthority>(true).unwrap();
+        state.init_extension::<NonTransferable>(true).unwrap();
+        state.base.is_initialized = true;
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn space_matches_the_token_program() {
+        let mut config = config();
+        let types = config.extension_types();
+        assert_eq!(
+            config.space(),
+            ExtensionType::get_account_len::<Mint>(&types)
+        );
+        assert_eq!(MintConfig::new(0, Pubkey::new_unique()).space(), Mint::LEN);
+
+        config.metadata = Some(metadata());
+        assert_eq!(
+            config.space(),
+            ExtensionType::get_account_len::<Mint>(&types)
+                + EXTENSION_HEADER_LEN
+                + METADATA_POINTER_LEN
+        );
+        assert_eq!(
+            config.space_with_metadata(),
+            config.space() + metadata().space()
+        );
+    }
+
+    #[test]
+    fn extensions_walks_the_tlv_entries() {
+        let config = config();
+        let data = mint_data(&config);
+
+        let types: Vec<_> =
+            extensions(&data).iter().map(|(extension, _)| *extension).collect();
+        let expected: Vec<_> =
+            config.extension_types().into_iter().map(u16::from).collect();
+        assert_eq!(types, expected);
+        assert_eq!(
+            extensions(&data)[0].1.len(),
+            ExtensionType::TransferFeeConfig.get_type_len()
+        );
+    }
+
+    #[test]
+    fn extensions_reads_types_newer_than_the_token_program() {
+        let mut data = mint_data(&config());
+        let pointer = [7; METADATA_POINTER_LEN];
+        data.extend_from_slice(&METADATA_POINTER_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&(pointer.len() as u16).to_le_bytes());
+        data.extend_from_slice(&pointer);
+
+        let extensions = extensions(&data);
+        assert_eq!(
+            extensions.last(),
+            Some(&(METADATA_POINTER_EXTENSION, &pointer[..]))
+        );
+    }
+
+    #[test]
+    fn extensions_stops_at_padding_and_truncation() {
+        assert!(extensions(&[0; Mint::LEN]).is_empty());
+
+        let mut data = vec![0; TokenAccount::LEN + 1];
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&8u16.to_le_bytes());
+        data.extend_from_slice(&[1, 2, 3]);
+        assert!(extensions(&data).is_empty());
+
+        let mut padded = vec![0; TokenAccount::LEN + 1];
+        padded.extend_from_slice(&[0; 8]);
+        assert!(extensions(&padded).is_empty());
+    }
+}
@@ -0,0 +1,441 @@
+//! Token-2022 metadata pointer and token metadata extensions, which store a
+//! mint's name, symbol and URI in the mint account itself. The
+//! spl-token-2022 version the course programs are pinned to predates these
+//! extensions, so their instructions and layouts are encoded here.
+
+use crate::{
+    token::{send, SendConfig, TokenError},
+    token_2022::extensions,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{keypair::Keypair, Signer},
+    system_instruction,
+};
+use std::{convert::Infallible, str::FromStr};
+
+/// Extension type of the metadata pointer in a mint's TLV data.
+pub const METADATA_POINTER_EXTENSION: u16 = 18;
+/// Extension type of the token metadata in a mint's TLV data.
+pub const TOKEN_METADATA_EXTENSION: u16 = 19;
+/// Size of the metadata pointer, its authority and metadata address.
+pub const METADATA_POINTER_LEN: usize = 64;
+/// Bytes each extension takes besides its value, its type and length.
+pub const EXTENSION_HEADER_LEN: usize = 4;
+
+const METADATA_POINTER_INSTRUCTION: u8 = 39;
+const METADATA_POINTER_INITIALIZE: u8 = 0;
+
+// First 8 bytes of the SHA-256 of the interface's instruction names, such
+// as `spl_token_metadata_interface:initialize_account`.
+const INITIALIZE_DISCRIMINATOR: [u8; 8] = [210, 225, 30, 162, 88, 184, 77, 141];
+const UPDATE_FIELD_DISCRIMINATOR: [u8; 8] =
+    [221, 233, 49, 45, 181, 202, 220, 200];
+
+/// Name, symbol and URI given to a mint when it is created.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MintMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+impl MintMetadata {
+    /// Size of the metadata once stored, with its extension header.
+    pub fn space(&self) -> usize {
+        EXTENSION_HEADER_LEN
+            + TokenMetadata {
+                update_authority: None,
+                mint: Pubkey::default(),
+                name: self.name.clone(),
+                symbol: self.symbol.clone(),
+                uri: self.uri.clone(),
+                additional_metadata: Vec::new(),
+            }
+            .packed_len()
+    }
+}
+
+/// A mint's name, symbol and URI as stored by the token metadata
+/// extension.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenMetadata {
+    /// Allowed to update the fields, `None` once the metadata is immutable.
+    pub update_authority: Option<Pubkey>,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    /// Fields beyond name, symbol and URI as key/value pairs.
+    pub additional_metadata: Vec<(String, String)>,
+}
+
+/// Layout of [`TokenMetadata`] in the mint, a missing update authority is
+/// stored as the default pubkey.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct PackedTokenMetadata {
+    update_authority: Pubkey,
+    mint: Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    additional_metadata: Vec<(String, String)>,
+}
+
+impl TokenMetadata {
+    pub fn unpack(data: &[u8]) -> Result<Self, std::io::Error> {
+        let packed = PackedTokenMetadata::try_from_slice(data)?;
+        Ok(TokenMetadata {
+            update_authority: Some(packed.update_authority)
+                .filter(|authority| *authority != Pubkey::default()),
+            mint: packed.mint,
+            name: packed.name,
+            symbol: packed.symbol,
+            uri: packed.uri,
+            additional_metadata: packed.additional_metadata,
+        })
+    }
+
+    /// Size of the metadata in the mint, without the extension header.
+    pub fn packed_len(&self) -> usize {
+        let string = |s: &str| 4 + s.len();
+        64 + string(&self.name)
+            + string(&self.symbol)
+            + string(&self.uri)
+            + 4
+            + self
+                .additional_metadata
+                .iter()
+                .map(|(key, value)| string(key) + string(value))
+                .sum::<usize>()
+    }
+
+    /// The metadata after setting `field` to `value`.
+    pub fn with_field(mut self, field: &Field, value: &str) -> Self {
+        let value = value.to_string();
+        match field {
+            Field::Name => self.name = value,
+            Field::Symbol => self.symbol = value,
+            Field::Uri => self.uri = value,
+            Field::Key(key) => {
+                match self
+                    .additional_metadata
+                    .iter_mut()
+                    .find(|(k, _)| k == key)
+                {
+                    Some((_, existing)) => *existing = value,
+                    None => self.additional_metadata.push((key.clone(), value)),
+                }
+            }
+        }
+        self
+    }
+}
+
+/// A metadata field, the known ones or any additional key.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize)]
+pub enum Field {
+    Name,
+    Symbol,
+    Uri,
+    Key(String),
+}
+
+impl FromStr for Field {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "name" => Field::Name,
+            "symbol" => Field::Symbol,
+            "uri" => Field::Uri,
+            key => Field::Key(key.to_string()),
+        })
+    }
+}
+
+/// Where a mint's metadata lives and who may move it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MetadataPointer {
+    pub authority: Option<Pubkey>,
+    pub metadata_address: Option<Pubkey>,
+}
+
+impl MetadataPointer {
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        if data.len() != METADATA_POINTER_LEN {
+            return None;
+        }
+        let pubkey = |bytes: &[u8]| {
+            Some(Pubkey::try_from(bytes).ok()?)
+                .filter(|pubkey| *pubkey != Pubkey::default())
+        };
+        Some(MetadataPointer {
+            authority: pubkey(&data[..32]),
+            metadata_address: pubkey(&data[32..]),
+        })
+    }
+}
+
+/// Points `mint` at metadata stored in `metadata_address`. Must come before
+/// the mint is initialized, in an account sized for the pointer.
+pub fn initialize_metadata_pointer(
+    mint: &Pubkey,
+    authority: Option<&Pubkey>,
+    metadata_address: Option<&Pubkey>,
+) -> Instruction {
+    let mut data =
+        vec![METADATA_POINTER_INSTRUCTION, METADATA_POINTER_INITIALIZE];
+    data.extend_from_slice(authority.copied().unwrap_or_default().as_ref());
+    data.extend_from_slice(
+        metadata_address.copied().unwrap_or_default().as_ref(),
+    );
+
+    Instruction::new_with_bytes(
+        spl_token_2022::id(),
+        &data,
+        vec![AccountMeta::new(*mint, false)],
+    )
+}
+
+/// Stores the metadata in `mint` itself, signed by its mint authority. The
+/// mint must already point at itself and hold the lamports for the larger
+/// account.
+pub fn initialize(
+    mint: &Pubkey,
+    update_authority: &Pubkey,
+    mint_authority: &Pubkey,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+) -> Instruction {
+    let mut data = INITIALIZE_DISCRIMINATOR.to_vec();
+    (name, symbol, uri).serialize(&mut data).expect("writing to a vec");
+
+    Instruction::new_with_bytes(
+        spl_token_2022::id(),
+        &data,
+        vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*update_authority, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+        ],
+    )
+}
+
+/// Sets one field of the metadata stored in `mint`, adding additional keys
+/// that are missing.
+pub fn update_field(
+    mint: &Pubkey,
+    update_authority: &Pubkey,
+    field: &Field,
+    value: &str,
+) -> Instruction {
+    let mut data = UPDATE_FIELD_DISCRIMINATOR.to_vec();
+    (field, value).serialize(&mut data).expect("writing to a vec");
+
+    Instruction::new_with_bytes(
+        spl_token_2022::id(),
+        &data,
+        vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*update_authority, true),
+        ],
+    )
+}
+
+/// Reads the metadata stored in a Token-2022 mint, `None` when it has none.
+pub fn get_token_metadata(
+    client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<Option<TokenMetadata>, TokenError> {
+    let data = client.get_account_data(mint)?;
+    let Some((_, value)) = extensions(&data)
+        .into_iter()
+        .find(|(extension, _)| *extension == TOKEN_METADATA_EXTENSION)
+    else {
+        return Ok(None);
+    };
+
+    let metadata = TokenMetadata::unpack(value).map_err(|e| {
+        TokenError::InvalidExtension(format!("token metadata: {e}"))
+    })?;
+    Ok(Some(metadata))
+}
+
+/// Sets `field` of the metadata stored in `mint`, first sending the mint
+/// the lamports its longer data needs to stay rent exempt.
+pub fn set_field(
+    client: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    update_authority: &Keypair,
+    field: &Field,
+    value: &str,
+    config: &SendConfig,
+) -> Result<Signature, TokenError> {
+    let instructions = set_field_instructions(
+        client,
+        &payer.pubkey(),
+        mint,
+        &update_authority.pubkey(),
+        field,
+        value,
+    )?;
+
+    send(client, &instructions, &[payer, update_authority], config)
+}
+
+/// Instructions of [`set_field`], signed by `payer` and `update_authority`.
+pub fn set_field_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    update_authority: &Pubkey,
+    field: &Field,
+    value: &str,
+) -> Result<Vec<Instruction>, TokenError> {
+    let account = client.get_account(mint)?;
+    let metadata = get_token_metadata(client, mint)?.ok_or_else(|| {
+        TokenError::InvalidExtension(format!("{mint} has no token metadata"))
+    })?;
+    let old_len = metadata.packed_len();
+    let new_len = metadata.with_field(field, value).packed_len();
+    let space = (account.data.len() + new_len).saturating_sub(old_len);
+    let rent = client.get_minimum_balance_for_rent_exemption(space)?;
+
+    let mut instructions = Vec::new();
+    if rent > account.lamports {
+        instructions.push(system_instruction::transfer(
+            payer,
+            mint,
+            rent - account.lamports,
+        ));
+    }
+    instructions.push(update_field(mint, update_authority, field, value));
+
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::hash;
+
+    fn metadata() -> TokenMetadata {
+        TokenMetadata {
+            update_authority: Some(Pubkey::new_unique()),
+            mint: Pubkey::new_unique(),
+            name: "Solana Course Token".to_string(),
+            symbol: "COURSE".to_string(),
+            uri: "https://example.com/course.json".to_string(),
+            additional_metadata: vec![("level".to_string(), "4".to_string())],
+        }
+    }
+
+    fn pack(metadata: &TokenMetadata) -> Vec<u8> {
+        PackedTokenMetadata {
+            update_authority: metadata.update_authority.unwrap_or_default(),
+            mint: metadata.mint,
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+            additional_metadata: metadata.additional_metadata.clone(),
+        }
+        .try_to_vec()
+        .unwrap()
+    }
+
+    #[test]
+    fn discriminators_hash_the_interface_names() {
+        let discriminator = |name: &str| {
+            let hash = hash(name.as_bytes()).to_bytes();
+            <[u8; 8]>::try_from(&hash[..8]).unwrap()
+        };
+        assert_eq!(
+            INITIALIZE_DISCRIMINATOR,
+            discriminator("spl_token_metadata_interface:initialize_account")
+        );
+        assert_eq!(
+            UPDATE_FIELD_DISCRIMINATOR,
+            discriminator("spl_token_metadata_interface:updating_field")
+        );
+    }
+
+    #[test]
+    fn packed_len_matches_the_layout() {
+        let metadata = metadata();
+        let packed = pack(&metadata);
+        assert_eq!(metadata.packed_len(), packed.len());
+        assert_eq!(TokenMetadata::unpack(&packed).unwrap(), metadata);
+
+        let mint_metadata = MintMetadata {
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+        };
+        let without_additional = TokenMetadata {
+            additional_metadata: Vec::new(),
+            ..metadata
+        };
+        assert_eq!(
+            mint_metadata.space(),
+            EXTENSION_HEADER_LEN + pack(&without_additional).len()
+        );
+    }
+
+    #[test]
+    fn missing_update_authority_is_the_default_pubkey() {
+        let metadata = TokenMetadata {
+            update_authority: None,
+            ..metadata()
+        };
+        let packed = pack(&metadata);
+        assert_eq!(&packed[..32], Pubkey::default().as_ref());
+        assert_eq!(TokenMetadata::unpack(&packed).unwrap(), metadata);
+    }
+
+    #[test]
+    fn with_field_replaces_or_adds() {
+        let metadata = metadata()
+            .with_field(&"name".parse().unwrap(), "Renamed")
+            .with_field(&"level".parse().unwrap(), "5")
+            .with_field(&"track".parse().unwrap(), "rust");
+        assert_eq!(metadata.name, "Renamed");
+        assert_eq!(
+            metadata.additional_metadata,
+            [
+                ("level".to_string(), "5".to_string()),
+                ("track".to_string(), "rust".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn encodes_instructions() {
+        let mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        let pointer =
+            initialize_metadata_pointer(&mint, Some(&authority), Some(&mint));
+        assert_eq!(pointer.data[..2], [39, 0]);
+        assert_eq!(
+            MetadataPointer::unpack(&pointer.data[2..]),
+            Some(MetadataPointer {
+                authority: Some(authority),
+                metadata_address: Some(mint),
+            })
+        );
+
+        let update = update_field(&mint, &authority, &Field::Uri, "u");
+        assert_eq!(update.data[..8], UPDATE_FIELD_DISCRIMINATOR);
+        // The variant index of `Uri`, then the value as a borsh string.
+        assert_eq!(update.data[8..], [2, 1, 0, 0, 0, b'u']);
+        assert!(update.accounts[1].is_signer);
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{
+    budget::ComputeBudgetConfig,
+    submit::{submit_with_compute_budget, SubmitConfig, SubmitOutcome},
+};
+use solana_client::{
+    client_error::Result as ClientResult, rpc_client::RpcClient,
+};
+use solana_sdk::{
+    pubkey::Pubkey, signer::keypair::Keypair, signer::Signer,
+    system_instruction,
+};
+
+pub fn send_sol(
+    client: &RpcClient,
+    amount: u64,
+    to: &Pubkey,
+    from: &Keypair,
+    budget: &ComputeBudgetConfig,
+    submit: &SubmitConfig,
+) -> ClientResult<SubmitOutcome> {
+    let instruction = system_instruction::transfer(&from.pubkey(), to, amount);
+
+    submit_with_compute_budget(
+        client,
+        &[instruction],
+        &from.pubkey(),
+        &[from],
+        budget,
+        submit,
+    )
+}
@@ -0,0 +1,509 @@
+use crate::{
+    fetch::MAX_MULTIPLE_ACCOUNTS, intro::StudentIntro, query::IntroQuery,
+};
+use borsh::BorshDeserialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::ClientError,
+    pubsub_client::{PubsubClient, PubsubClientError},
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+};
+use solana_sdk::{
+    account::Account, clock::Slot, commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    ops::ControlFlow,
+    thread::sleep,
+    time::Duration,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+
+    #[error("giving up after {attempts} failed connection attempts: {source}")]
+    Pubsub {
+        attempts: u32,
+        source: PubsubClientError,
+    },
+
+    #[error("giving up after {attempts} failed catch-up scans: {source}")]
+    CatchUp {
+        attempts: u32,
+        source: ClientError,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    /// The account changed, or no longer matches the watched query.
+    Updated,
+    Closed,
+}
+
+#[derive(Debug)]
+pub struct IntroUpdate {
+    pub kind: ChangeKind,
+    pub pubkey: Pubkey,
+    /// Slot of the notification, `None` for changes found by a catch-up scan.
+    pub slot: Option<Slot>,
+    pub lamports: u64,
+    pub data_len: usize,
+    /// Decoded intro, or the reason decoding failed. Always an error for
+    /// closed accounts.
+    pub intro: Result<StudentIntro, String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct WatchConfig {
+    pub ws_url: String,
+    pub query: IntroQuery,
+    pub commitment: CommitmentConfig,
+    /// Report accounts that already exist when the watch starts.
+    pub include_existing: bool,
+    pub min_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Consecutive failed connection attempts before giving up, `None` to
+    /// retry forever.
+    pub max_retries: Option<u32>,
+}
+
+impl WatchConfig {
+    pub fn new(ws_url: impl Into<String>) -> Self {
+        Self {
+            ws_url: ws_url.into(),
+            query: IntroQuery::default(),
+            commitment: CommitmentConfig::confirmed(),
+            include_existing: false,
+            min_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_retries: None,
+        }
+    }
+}
+
+/// Follows a program's accounts over a `programSubscribe` websocket.
+///
+/// When the socket drops the watcher reconnects with exponential backoff and
+/// rescans the program over RPC, so changes made while disconnected are still
+/// reported.
+pub struct IntroWatcher<'a> {
+    client: &'a RpcClient,
+    program_id: Pubkey,
+    config: WatchConfig,
+    seen: HashMap<Pubkey, u64>,
+}
+
+impl<'a> IntroWatcher<'a> {
+    pub fn new(
+        client: &'a RpcClient,
+        program_id: Pubkey,
+        config: WatchConfig,
+    ) -> Self {
+        Self {
+            client,
+            program_id,
+            config,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Calls `on_update` for every change until it returns
+    /// [`ControlFlow::Break`] or reconnecting fails `max_retries` times in a
+    /// row. A failed catch-up scan counts as a failed connection attempt.
+    pub fn run<F>(&mut self, mut on_update: F) -> Result<(), WatchError>
+    where
+        F: FnMut(IntroUpdate) -> ControlFlow<()>,
+    {
+        let mut connected_before = false;
+        let mut backoff = Backoff::new(&self.config);
+
+        loop {
+            let (mut subscription, receiver) =
+                match PubsubClient::program_subscribe(
+                    &self.config.ws_url,
+                    &self.program_id,
+                    Some(self.program_accounts_config()),
+                ) {
+                    Ok(subscription) => subscription,
+                    Err(source) => {
+                        backoff.fail().map_err(|attempts| {
+                            WatchError::Pubsub {
+                                attempts,
+                                source,
+                            }
+                        })?;
+                        continue;
+                    }
+                };
+
+            // Subscribe before scanning so nothing slips in between the two.
+            let report = connected_before || self.config.include_existing;
+            match self.resync(report, &mut on_update, &mut backoff)? {
+                Some(ControlFlow::Continue(())) => connected_before = true,
+                Some(ControlFlow::Break(())) => {
+                    let _ = subscription.send_unsubscribe();
+                    return Ok(());
+                }
+                None => {
+                    let _ = subscription.shutdown();
+                    continue;
+                }
+            }
+
+            while let Ok(response) = receiver.recv() {
+                let Some(account) = response.value.account.decode::<Account>()
+                else {
+                    continue;
+                };
+                let Ok(pubkey) = response.value.pubkey.parse::<Pubkey>() else {
+                    continue;
+                };
+
+                if let Some(update) =
+                    self.observe(pubkey, &account, Some(response.context.slot))
+                {
+                    if on_update(update).is_break() {
+                        let _ = subscription.send_unsubscribe();
+                        return Ok(());
+                    }
+                }
+            }
+
+            let _ = subscription.shutdown();
+        }
+    }
+
+    /// Runs a catch-up scan after connecting, resetting `backoff` when it
+    /// succeeds. A failed scan is recorded in `backoff` and returns `None`
+    /// so the caller reconnects, until `max_retries` is exceeded.
+    fn resync<F>(
+        &mut self,
+        report: bool,
+        on_update: &mut F,
+        backoff: &mut Backoff,
+    ) -> Result<Option<ControlFlow<()>>, WatchError>
+    where
+        F: FnMut(IntroUpdate) -> ControlFlow<()>,
+    {
+        match self.catch_up(report, on_update) {
+            Ok(flow) => {
+                backoff.reset();
+                Ok(Some(flow))
+            }
+            Err(source) => {
+                backoff.fail().map_err(|attempts| WatchError::CatchUp {
+                    attempts,
+                    source,
+                })?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Rescans the program and reports anything that changed since the last
+    /// observed state.
+    fn catch_up<F>(
+        &mut self,
+        report: bool,
+        on_update: &mut F,
+    ) -> Result<ControlFlow<()>, ClientError>
+    where
+        F: FnMut(IntroUpdate) -> ControlFlow<()>,
+    {
+        let accounts = self.client.get_program_accounts_with_config(
+            &self.program_id,
+            self.program_accounts_config(),
+        )?;
+
+        let present =
+            accounts.iter().map(|(pubkey, _)| *pubkey).collect::<HashSet<_>>();
+        let missing = self
+            .seen
+            .keys()
+            .filter(|key| !present.contains(key))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for (pubkey, account) in accounts {
+            if let Some(update) = self.observe(pubkey, &account, None) {
+                if report && on_update(update).is_break() {
+                    return Ok(ControlFlow::Break(()));
+                }
+            }
+        }
+
+        // An account missing from a filtered scan may only have stopped
+        // matching the filter, it is only closed if it no longer exists.
+        for chunk in missing.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = self
+                .client
+                .get_multiple_accounts_with_commitment(
+                    chunk,
+                    self.config.commitment,
+                )?
+                .value;
+
+            for (pubkey, account) in chunk.iter().copied().zip(accounts) {
+                let update = match account {
+                    Some(account) => {
+                        let update = self.observe(pubkey, &account, None);
+                        self.seen.remove(&pubkey);
+                        match update {
+                            Some(update) => update,
+                            None => continue,
+                        }
+                    }
+                    None => {
+                        self.seen.remove(&pubkey);
+                        IntroUpdate {
+                            kind: ChangeKind::Closed,
+                            pubkey,
+                            slot: None,
+                            lamports: 0,
+                            data_len: 0,
+                            intro: Err("account closed".to_string()),
+                        }
+                    }
+                };
+                if report && on_update(update).is_break() {
+                    return Ok(ControlFlow::Break(()));
+                }
+            }
+        }
+
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Records the account state, returning an update if it is new or
+    /// different from what was seen last.
+    fn observe(
+        &mut self,
+        pubkey: Pubkey,
+        account: &Account,
+        slot: Option<Slot>,
+    ) -> Option<IntroUpdate> {
+        let mut hasher = DefaultHasher::new();
+        account.lamports.hash(&mut hasher);
+        account.data.hash(&mut hasher);
+        let state = hasher.finish();
+
+        let kind = match self.seen.insert(pubkey, state) {
+            None => ChangeKind::Created,
+            Some(previous) if previous != state => ChangeKind::Updated,
+            Some(_) => return None,
+        };
+
+        Some(IntroUpdate {
+            kind,
+            pubkey,
+            slot,
+            lamports: account.lamports,
+            data_len: account.data.len(),
+            intro: StudentIntro::deserialize(&mut account.data.as_slice())
+                .map_err(|e| e.to_string()),
+        })
+    }
+
+    fn program_accounts_config(&self) -> RpcProgramAccountsConfig {
+        RpcProgramAccountsConfig {
+            filters: self.config.query.rpc_filters(),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(self.config.commitment),
+                ..RpcAccountInfoConfig::default()
+            },
+            with_context: None,
+        }
+    }
+}
+
+/// Consecutive failures of the watch and the delay before the next attempt.
+struct Backoff {
+    failures: u32,
+    delay: Duration,
+    min: Duration,
+    max: Duration,
+    max_retries: Option<u32>,
+}
+
+impl Backoff {
+    fn new(config: &WatchConfig) -> Self {
+        Self {
+            failures: 0,
+            delay: config.min_backoff,
+            min: config.min_backoff,
+            max: config.max_backoff,
+            max_retries: config.max_retries,
+        }
+    }
+
+    /// Records a failure and waits before the next attempt, or returns the
+    /// number of attempts once there are no retries left.
+    fn fail(&mut self) -> Result<(), u32> {
+        self.failures += 1;
+        if self.max_retries.is_some_and(|max| self.failures > max) {
+            return Err(self.failures);
+        }
+
+        sleep(self.delay);
+        self.delay = (self.delay * 2).min(self.max);
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.failures = 0;
+        self.delay = self.min;
+    }
+}
+
+/// Derives the websocket endpoint from an RPC url the same way the Solana
+/// CLI does: `http` becomes `ws`, `https` becomes `wss`, and an explicit port
+/// is bumped by one, e.g. `8899` to `8900` for `solana-test-validator`.
+pub fn websocket_url(rpc_url: &str) -> String {
+    let (scheme, rest) = match rpc_url.split_once("://") {
+        Some(("https", rest)) => ("wss", rest),
+        Some((_, rest)) => ("ws", rest),
+        None => ("ws", rpc_url),
+    };
+
+    let (host, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) => match port.parse::<u16>() {
+            Ok(port) => format!("{name}:{}", port.saturating_add(1)),
+            Err(_) => host.to_string(),
+        },
+        None => host.to_string(),
+    };
+
+    format!("{scheme}://{host}{path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::rpc_request::RpcRequest;
+    use std::collections::HashMap;
+
+    fn config() -> WatchConfig {
+        WatchConfig {
+            min_backoff: Duration::ZERO,
+            max_retries: Some(1),
+            ..WatchConfig::new("ws://127.0.0.1:8900")
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_max_and_gives_up() {
+        let mut backoff = Backoff::new(&WatchConfig {
+            min_backoff: Duration::from_nanos(1),
+            max_backoff: Duration::from_nanos(3),
+            max_retries: Some(2),
+            ..config()
+        });
+
+        assert_eq!(backoff.fail(), Ok(()));
+        assert_eq!(backoff.delay, Duration::from_nanos(2));
+        assert_eq!(backoff.fail(), Ok(()));
+        assert_eq!(backoff.delay, Duration::from_nanos(3));
+        assert_eq!(backoff.fail(), Err(3));
+
+        backoff.reset();
+        assert_eq!(backoff.failures, 0);
+        assert_eq!(backoff.delay, Duration::from_nanos(1));
+    }
+
+    #[test]
+    fn failed_catch_up_scans_are_retried() {
+        // Answers the first scan, then fails every request.
+        let client = RpcClient::new_mock_with_mocks(
+            "fails".to_string(),
+            HashMap::from([
+                (
+                    RpcRequest::GetVersion,
+                    serde_json::json!({ "solana-core": "1.16.1" }),
+                ),
+                (RpcRequest::GetProgramAccounts, serde_json::json!([])),
+            ]),
+        );
+        let mut watcher =
+            IntroWatcher::new(&client, Pubkey::new_unique(), config());
+        let mut backoff = Backoff::new(&watcher.config);
+        let mut on_update = |_| ControlFlow::Continue(());
+
+        assert!(matches!(
+            watcher.resync(true, &mut on_update, &mut backoff),
+            Ok(Some(ControlFlow::Continue(())))
+        ));
+        assert!(matches!(
+            watcher.resync(true, &mut on_update, &mut backoff),
+            Ok(None)
+        ));
+        assert!(matches!(
+            watcher.resync(true, &mut on_update, &mut backoff),
+            Err(WatchError::CatchUp {
+                attempts: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn successful_catch_up_resets_the_backoff() {
+        let client = RpcClient::new_mock("succeeds".to_string());
+        let mut watcher =
+            IntroWatcher::new(&client, Pubkey::new_unique(), config());
+        let mut backoff = Backoff::new(&watcher.config);
+        backoff.fail().unwrap();
+
+        let mut updates = Vec::new();
+        let flow = watcher
+            .resync(
+                true,
+                &mut |update| {
+                    updates.push(update.kind);
+                    ControlFlow::Continue(())
+                },
+                &mut backoff,
+            )
+            .unwrap();
+
+        assert_eq!(flow, Some(ControlFlow::Continue(())));
+        assert_eq!(backoff.failures, 0);
+        assert_eq!(updates, [ChangeKind::Created]);
+    }
+
+    #[test]
+    fn websocket_url_follows_the_cli() {
+        assert_eq!(
+            websocket_url("https://api.devnet.solana.com"),
+            "wss://api.devnet.solana.com"
+        );
+        assert_eq!(
+            websocket_url("http://127.0.0.1:8899"),
+            "ws://127.0.0.1:8900"
+        );
+        assert_eq!(
+            websocket_url("https://rpc.example.com:443/v1/key"),
+            "wss://rpc.example.com:444/v1/key"
+        );
+        assert_eq!(websocket_url("localhost:8899"), "ws://localhost:8900");
+    }
+
+    #[test]
+    fn websocket_url_keeps_unparsable_ports() {
+        assert_eq!(websocket_url("http://[::1]/rpc"), "ws://[::1]/rpc");
+        assert_eq!(websocket_url("http://host:port"), "ws://host:port");
+    }
+}
@@ -0,0 +1,106 @@
+//! Runs the watcher against a local validator with the student intro
+//! program deployed, for example
+//!
+//! ```text
+//! solana-test-validator --url devnet \
+//!     --clone-upgradeable-program HdE95RSVsdb315jfJtaykXhXY478h53X6okDupVfY9yf
+//! cargo test -p course --test watch -- --ignored
+//! ```
+//!
+//! `COURSE_TEST_RPC_URL` and `COURSE_TEST_PROGRAM_ID` point the test at
+//! another validator or program.
+
+use course::{
+    airdrop::ensure_funded,
+    budget::{send_with_compute_budget, ComputeBudgetConfig},
+    intro::{
+        create_student_intro_instruction, update_student_intro_instruction,
+    },
+    query::IntroQuery,
+    watch::{websocket_url, ChangeKind, IntroWatcher, WatchConfig},
+    STUDENT_INTRO_PROGRAM_ID,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signer::{keypair::Keypair, Signer},
+};
+use std::{ops::ControlFlow, sync::mpsc, thread, time::Duration};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[test]
+#[ignore = "needs a solana-test-validator running the student intro program"]
+fn reports_created_and_updated_intros() {
+    let rpc_url = std::env::var("COURSE_TEST_RPC_URL")
+        .unwrap_or_else(|_| "http://127.0.0.1:8899".to_string());
+    let program_id: Pubkey = std::env::var("COURSE_TEST_PROGRAM_ID")
+        .unwrap_or_else(|_| STUDENT_INTRO_PROGRAM_ID.to_string())
+        .parse()
+        .unwrap();
+    let client =
+        RpcClient::new_with_commitment(&rpc_url, CommitmentConfig::confirmed());
+
+    let payer = Keypair::new();
+    ensure_funded(&client, &payer.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    let pda =
+        Pubkey::find_program_address(&[payer.pubkey().as_ref()], &program_id).0;
+    let name = payer.pubkey().to_string();
+
+    let config = WatchConfig {
+        query: IntroQuery::new().name(&name),
+        include_existing: true,
+        max_retries: Some(3),
+        ..WatchConfig::new(websocket_url(&rpc_url))
+    };
+    let (sender, updates) = mpsc::channel();
+    let watch_url = rpc_url.clone();
+    thread::spawn(move || {
+        let client = RpcClient::new_with_commitment(
+            watch_url,
+            CommitmentConfig::confirmed(),
+        );
+        IntroWatcher::new(&client, program_id, config)
+            .run(|update| match sender.send(update) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(_) => ControlFlow::Break(()),
+            })
+            .unwrap();
+    });
+
+    let send = |instruction| {
+        send_with_compute_budget(
+            &client,
+            &[instruction],
+            &payer.pubkey(),
+            &[&payer],
+            &ComputeBudgetConfig::default(),
+        )
+        .unwrap()
+    };
+    send(create_student_intro_instruction(
+        &program_id,
+        &payer.pubkey(),
+        &name,
+        "first",
+    ));
+
+    let created = updates.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(created.kind, ChangeKind::Created);
+    assert_eq!(created.pubkey, pda);
+    assert_eq!(created.intro.unwrap().message, "first");
+
+    send(update_student_intro_instruction(
+        &program_id,
+        &payer.pubkey(),
+        &name,
+        "second",
+    ));
+
+    let updated = updates.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(updated.kind, ChangeKind::Updated);
+    assert_eq!(updated.pubkey, pda);
+    assert_eq!(updated.intro.unwrap().message, "second");
+}
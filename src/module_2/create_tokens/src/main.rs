@@ -1,173 +1,156 @@
+use clap::Parser;
+use course::{
+    config::{self, Cluster},
+    simulate::simulate,
+    token::{self, SendConfig, TokenError},
+    token_2022::{self, MintConfig},
+    token_metadata::{self, MintMetadata},
+};
 use solana_client::rpc_client::RpcClient;
-use solana_program::program_pack::Pack;
 use solana_sdk::{
+    signature::Signature,
     signer::{keypair::Keypair, Signer},
-    transaction::Transaction,
-};
-use spl_token::{
-    instruction,
-    state::{Account, Mint},
 };
-use std::str::FromStr;
-use std::u8;
 
-fn initialize_key_pair() -> Keypair {
-    let private_key_env = std::env::var("PRIVATE_KEY").unwrap();
+#[derive(Parser)]
+struct Args {
+    /// Config profile to use, defaults to the active one
+    #[clap(long)]
+    profile: Option<String>,
 
-    let private_key: Vec<_> =
-        private_key_env.split(',').flat_map(u8::from_str).collect();
-
-    Keypair::from_bytes(private_key.as_slice()).unwrap()
+    /// Simulate creating the mint and print its logs instead of sending it
+    #[clap(long)]
+    simulate: bool,
 }
 
-fn create_mint(
-    client: &RpcClient,
-    payer: &Keypair,
-    owner: &Keypair,
-    mint_account: &Keypair,
+fn report(
+    cluster: &Cluster,
+    step: &str,
+    result: Result<Signature, TokenError>,
 ) {
-    let token_program = &spl_token::id();
-    let mint_rent =
-        client.get_minimum_balance_for_rent_exemption(Account::LEN).unwrap();
-
-    let create_account_instr =
-        solana_program::system_instruction::create_account(
-            &payer.pubkey(),
-            &mint_account.pubkey(),
-            mint_rent,
-            Mint::LEN as u64,
-            token_program,
-        );
-
-    let init_mint_instr = instruction::initialize_mint(
-        token_program,
-        &mint_account.pubkey(),
-        &owner.pubkey(),
-        None,
-        2,
-    )
-    .unwrap();
-
-    let latest_blockhash = client.get_latest_blockhash().unwrap();
-    let mint_transaction = Transaction::new_signed_with_payer(
-        &[create_account_instr, init_mint_instr],
-        Some(&payer.pubkey()),
-        &[payer, mint_account],
-        latest_blockhash,
-    );
-
-    match client.send_and_confirm_transaction(&mint_transaction) {
+    match result {
         Ok(sig) => {
-            println!("Create Mint - View your transaction on the Solana Explorer at:\nhttps://explorer.solana.com/tx/{sig}?cluster=devnet");
+            println!(
+                "{step} - View your transaction on the Solana Explorer at:\n{}",
+                cluster.explorer_transaction_url(&sig)
+            );
         }
         Err(e) => println!("Error: {e}"),
     }
 }
 
-fn create_token_account(
+fn simulate_create_mint(
     client: &RpcClient,
     payer: &Keypair,
-    token_account: &Keypair,
     mint_account: &Keypair,
+    config: &MintConfig,
 ) {
-    let token_program = &spl_token::id();
-    let mint_rent =
-        client.get_minimum_balance_for_rent_exemption(Account::LEN).unwrap();
-    let token_account_instr =
-        solana_program::system_instruction::create_account(
-            &payer.pubkey(),
-            &token_account.pubkey(),
-            mint_rent,
-            Account::LEN as u64,
-            token_program,
-        );
-
-    let account = Keypair::new();
-    let init_account_instr = instruction::initialize_account(
-        token_program,
-        &token_account.pubkey(),
+    let simulation = token_2022::create_mint_instructions(
+        client,
+        &payer.pubkey(),
         &mint_account.pubkey(),
-        &account.pubkey(),
+        config,
     )
-    .unwrap();
-
-    let latest_blockhash = client.get_latest_blockhash().unwrap();
-    let mint_transaction = Transaction::new_signed_with_payer(
-        &[token_account_instr, init_account_instr],
-        Some(&payer.pubkey()),
-        &[payer, token_account],
-        latest_blockhash,
-    );
-
-    match client.send_and_confirm_transaction(&mint_transaction) {
-        Ok(sig) => {
-            println!("Create token account - View your transaction on the Solana Explorer at:\nhttps://explorer.solana.com/tx/{sig}?cluster=devnet");
-        }
-        Err(e) => println!("Error: {e}"),
-    }
-}
-
-fn mint_tokens(
-    client: &RpcClient,
-    mint_amount: u64,
-    payer: &Keypair,
-    mint_account: &Keypair,
-    token_account: &Keypair,
-    owner: &Keypair,
-) {
-    let token_program = &spl_token::id();
-    let mint_instr = instruction::mint_to(
-        token_program,
-        &mint_account.pubkey(),
-        &token_account.pubkey(),
-        &owner.pubkey(),
-        &[],
-        mint_amount,
-    )
-    .unwrap();
-
-    let latest_blockhash = client.get_latest_blockhash().unwrap();
-    let mint_transaction = Transaction::new_signed_with_payer(
-        &[mint_instr],
-        Some(&payer.pubkey()),
-        &[payer, owner],
-        latest_blockhash,
-    );
-
-    match client.send_and_confirm_transaction(&mint_transaction) {
-        Ok(sig) => {
-            println!("Mint tokens - View your transaction on the Solana Explorer at:\nhttps://explorer.solana.com/tx/{sig}?cluster=devnet");
+    .map_err(|e| e.to_string())
+    .and_then(|instructions| {
+        simulate(client, &instructions, &payer.pubkey(), &[payer, mint_account])
+            .map_err(|e| e.to_string())
+    });
+
+    match simulation {
+        Ok(simulation) => {
+            for log in simulation.logs {
+                println!("{log}");
+            }
+            if let Some(units) = simulation.units_consumed {
+                println!("Compute units consumed: {units}");
+            }
         }
         Err(e) => println!("Error: {e}"),
     }
 }
 
 fn main() -> web3::Result<()> {
-    let url = "https://api.devnet.solana.com";
-    let client = RpcClient::new(url);
-    let payer = initialize_key_pair();
+    let args = Args::parse();
+    let profile = match config::load_profile(args.profile.as_deref()) {
+        Ok(profile) => profile,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let client =
+        RpcClient::new_with_commitment(&profile.rpc_url, profile.commitment);
+    let payer = match profile.load_keypair() {
+        Ok(payer) => payer,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let send_config = SendConfig::default();
 
     let mint_account = Keypair::new();
-    let owner = Keypair::new();
-    let token_account = Keypair::new();
+    let mint_config = MintConfig {
+        metadata: Some(MintMetadata {
+            name: "Solana Course Token".to_string(),
+            symbol: "COURSE".to_string(),
+            uri: String::new(),
+        }),
+        ..MintConfig::new(2, payer.pubkey())
+    };
+
+    // The later steps need the mint on chain, so a dry run stops after
+    // simulating its creation.
+    if args.simulate {
+        simulate_create_mint(&client, &payer, &mint_account, &mint_config);
+        return Ok(());
+    }
 
-    create_mint(&client, &payer, &owner, &mint_account);
+    report(
+        &profile.cluster,
+        "Create Mint",
+        token_2022::create_mint(
+            &client,
+            &payer,
+            &mint_account,
+            &mint_config,
+            &send_config,
+        ),
+    );
 
-    create_token_account(&client, &payer, &token_account, &mint_account);
+    let metadata =
+        token_metadata::get_token_metadata(&client, &mint_account.pubkey())
+            .unwrap();
+    println!("Metadata: {metadata:?}");
 
-    let mint_amount = 5;
-    mint_tokens(
+    let token_account = token::get_or_create_associated_token_account(
         &client,
-        mint_amount,
         &payer,
-        &mint_account,
-        &token_account,
-        &owner,
+        &payer.pubkey(),
+        &mint_account.pubkey(),
+        &send_config,
+    )
+    .unwrap();
+    println!("Token account: {token_account}");
+
+    let mint_amount = 5;
+    report(
+        &profile.cluster,
+        "Mint tokens",
+        token::mint_to_wallet(
+            &client,
+            &payer,
+            &mint_account.pubkey(),
+            &payer.pubkey(),
+            &payer,
+            mint_amount,
+            &send_config,
+        ),
     );
 
-    let token_account_info =
-        client.get_account(&token_account.pubkey()).unwrap();
-    let token_account_data = Account::unpack(&token_account_info.data).unwrap();
+    let token_account_data =
+        token::get_token_account(&client, &token_account).unwrap();
     assert_eq!(mint_amount, token_account_data.amount);
 
     Ok(())
@@ -1,53 +1,115 @@
+use clap::Parser;
+use course::{
+    airdrop::ensure_funded,
+    budget::ComputeBudgetConfig,
+    config::{self, Profile, HELLO_WORLD_PROGRAM},
+    simulate::simulate,
+    submit::{submit_with_compute_budget, SubmitConfig, SubmitOutcome},
+};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
-    instruction, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
-    signer::keypair::Keypair, signer::Signer, transaction::Transaction,
+    instruction, native_token::LAMPORTS_PER_SOL, signer::keypair::Keypair,
+    signer::Signer,
 };
-use std::str::FromStr;
-use std::u8;
-
-fn initialize_key_pair() -> Keypair {
-    let private_key_env = std::env::var("PRIVATE_KEY").unwrap();
 
-    let private_key: Vec<_> =
-        private_key_env.split(',').flat_map(u8::from_str).collect();
+#[derive(Parser)]
+struct Args {
+    /// Config profile to use, defaults to the active one
+    #[clap(long)]
+    profile: Option<String>,
 
-    Keypair::from_bytes(private_key.as_slice()).unwrap()
+    /// Simulate the transaction and print its logs instead of sending it
+    #[clap(long)]
+    simulate: bool,
 }
 
-fn say_hello(client: RpcClient, payer: Keypair) {
-    let program_id =
-        Pubkey::from_str("9whiGXV1XPWdk1BaNTeheey9xuJk37U4amvqDnX1gpcW")
-            .unwrap();
+fn say_hello(
+    client: RpcClient,
+    profile: &Profile,
+    payer: Keypair,
+    dry_run: bool,
+) {
+    let Some(program_id) = profile.program_id(HELLO_WORLD_PROGRAM) else {
+        println!(
+            "Error: profile `{}` has no `{HELLO_WORLD_PROGRAM}` program id",
+            profile.name
+        );
+        return;
+    };
 
     let instruction =
         instruction::Instruction::new_with_bytes(program_id, &[], vec![]);
 
-    let latest_blockhash = client.get_latest_blockhash().unwrap();
-    let transaction = Transaction::new_signed_with_payer(
+    if dry_run {
+        match simulate(&client, &[instruction], &payer.pubkey(), &[&payer]) {
+            Ok(simulation) => {
+                for log in simulation.logs {
+                    println!("{log}");
+                }
+                if let Some(units) = simulation.units_consumed {
+                    println!("Compute units consumed: {units}");
+                }
+            }
+            Err(e) => println!("Error: {e}"),
+        }
+        return;
+    }
+
+    if profile.cluster.supports_airdrops() {
+        if let Err(e) =
+            ensure_funded(&client, &payer.pubkey(), LAMPORTS_PER_SOL)
+        {
+            println!("Error: {e}");
+            return;
+        }
+    }
+
+    match submit_with_compute_budget(
+        &client,
         &[instruction],
-        Some(&payer.pubkey()),
+        &payer.pubkey(),
         &[&payer],
-        latest_blockhash,
-    );
-
-    match client.send_and_confirm_transaction(&transaction) {
-        Ok(sig) => {
-            println!("You can view your transaction on the Solana Explorer at:\nhttps://explorer.solana.com/tx/{sig}?cluster=devnet");
+        &ComputeBudgetConfig::default(),
+        &SubmitConfig::default(),
+    ) {
+        Ok(SubmitOutcome::Confirmed(sig)) => {
+            println!(
+                "You can view your transaction on the Solana Explorer at:\n{}",
+                profile.cluster.explorer_transaction_url(&sig)
+            );
+        }
+        Ok(SubmitOutcome::Failed {
+            signature,
+            err,
+        }) => println!("Transaction {signature} failed: {err}"),
+        Ok(SubmitOutcome::Expired(sig)) => {
+            println!("Transaction {sig} expired before it landed")
         }
         Err(e) => println!("Error: {e}"),
     }
 }
 
 fn main() -> web3::Result<()> {
-    let url = "https://api.devnet.solana.com";
-    let client = RpcClient::new(url);
-
-    let payer = initialize_key_pair();
+    let args = Args::parse();
+    let profile = match config::load_profile(args.profile.as_deref()) {
+        Ok(profile) => profile,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let client =
+        RpcClient::new_with_commitment(&profile.rpc_url, profile.commitment);
 
-    client.request_airdrop(&payer.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    let payer = match profile.load_keypair() {
+        Ok(payer) => payer,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    say_hello(client, payer);
+    say_hello(client, &profile, payer, args.simulate);
 
     Ok(())
 }
@@ -1,6 +1,9 @@
 use crate::error::StudentIntroError;
 use crate::instruction::IntroInstruction;
-use crate::state::{Reply, ReplyCounter, StudentInfo};
+use crate::state::{
+    Reply, ReplyCounter, StudentInfo, COUNTER_DISCRIMINATOR,
+    REPLY_DISCRIMINATOR, STUDENT_INFO_DISCRIMINATOR,
+};
 use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
@@ -61,7 +64,7 @@ pub fn add_student_intro(
         return Err(StudentIntroError::InvalidPDA.into());
     }
 
-    let studentinfo_discriminator = "studentinfo";
+    let studentinfo_discriminator = STUDENT_INFO_DISCRIMINATOR;
     let total_len: usize = (4 + studentinfo_discriminator.len())
         + 1
         + (4 + name.len())
@@ -103,7 +106,7 @@ pub fn add_student_intro(
     account_data.is_initialized = true;
     account_data.serialize(&mut &mut user_account.data.borrow_mut()[..])?;
 
-    let counter_discriminator = "counter";
+    let counter_discriminator = COUNTER_DISCRIMINATOR;
     let counter_len: usize = (4 + counter_discriminator.len()) + 1 + 1;
 
     let rent = Rent::get()?;
@@ -218,7 +221,7 @@ pub fn add_reply(
         try_from_slice_unchecked::<ReplyCounter>(&reply_counter.data.borrow())
             .unwrap();
 
-    let reply_discriminator = "reply";
+    let reply_discriminator = REPLY_DISCRIMINATOR;
     let account_len: usize =
         (4 + reply_discriminator.len()) + 1 + 32 + (4 + reply.len());
 
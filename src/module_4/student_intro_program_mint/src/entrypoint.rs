@@ -1,9 +1,9 @@
 use solana_program::{
     account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg,
-    pubkey::Pubkey,
+    program_error::PrintProgramError, pubkey::Pubkey,
 };
 
-use crate::processor;
+use crate::{error::StudentIntroError, processor};
 
 entrypoint!(process_instruction);
 
@@ -19,7 +19,12 @@ fn process_instruction(
         instruction_data
     );
 
-    processor::process_instruction(program_id, accounts, instruction_data)?;
+    if let Err(error) =
+        processor::process_instruction(program_id, accounts, instruction_data)
+    {
+        error.print::<StudentIntroError>();
+        return Err(error);
+    }
 
     Ok(())
 }
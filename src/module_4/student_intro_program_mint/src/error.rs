@@ -1,7 +1,12 @@
-use solana_program::program_error::ProgramError;
+use num_traits::FromPrimitive;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
 pub enum StudentIntroError {
     #[error("Account not initialized yet")]
     UninitializedAccount,
@@ -21,3 +26,77 @@ impl From<StudentIntroError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+impl TryFrom<u32> for StudentIntroError {
+    type Error = ProgramError;
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        match code {
+            0 => Ok(Self::UninitializedAccount),
+            1 => Ok(Self::InvalidPDA),
+            2 => Ok(Self::InvalidDataLength),
+            3 => Ok(Self::IncorrectAccount),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    }
+}
+
+/// Lets [`ProgramError::print`] decode custom codes back into this enum.
+impl FromPrimitive for StudentIntroError {
+    fn from_i64(n: i64) -> Option<Self> {
+        u32::try_from(n).ok().and_then(|code| Self::try_from(code).ok())
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        u32::try_from(n).ok().and_then(|code| Self::try_from(code).ok())
+    }
+}
+
+impl<T> DecodeError<T> for StudentIntroError {
+    fn type_of() -> &'static str {
+        "StudentIntroError"
+    }
+}
+
+impl PrintProgramError for StudentIntroError {
+    fn print<E>(&self)
+    where
+        E: 'static
+            + std::error::Error
+            + DecodeError<E>
+            + PrintProgramError
+            + FromPrimitive,
+    {
+        msg!("Error: {} ({:?})", self, self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for error in [
+            StudentIntroError::UninitializedAccount,
+            StudentIntroError::InvalidPDA,
+            StudentIntroError::InvalidDataLength,
+            StudentIntroError::IncorrectAccount,
+        ] {
+            let ProgramError::Custom(code) = ProgramError::from(error) else {
+                panic!("{error:?} is not a custom error");
+            };
+            assert_eq!(StudentIntroError::try_from(code), Ok(error));
+            assert_eq!(StudentIntroError::from_u64(code.into()), Some(error));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_codes() {
+        assert_eq!(
+            StudentIntroError::try_from(4),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(StudentIntroError::from_i64(-1), None);
+    }
+}
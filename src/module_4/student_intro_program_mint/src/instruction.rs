@@ -1,6 +1,7 @@
 use borsh::BorshDeserialize;
 use solana_program::program_error::ProgramError;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum IntroInstruction {
     CreateAccount {
         name: String,
@@ -14,6 +15,19 @@ pub enum IntroInstruction {
         reply: String,
     },
     InitializeMint,
+    /// Creates the Token-2022 metadata mint and stores its name, symbol and
+    /// URI, with the caller as the authority allowed to change them.
+    InitializeMetadataMint {
+        name: String,
+        symbol: String,
+        uri: String,
+    },
+    /// Sets `name`, `symbol`, `uri` or any additional key of the metadata
+    /// mint.
+    UpdateMintMetadata {
+        field: String,
+        value: String,
+    },
 }
 
 #[derive(BorshDeserialize)]
@@ -27,6 +41,19 @@ struct ReplyPayload {
     reply: String,
 }
 
+#[derive(BorshDeserialize)]
+struct MetadataPayload {
+    name: String,
+    symbol: String,
+    uri: String,
+}
+
+#[derive(BorshDeserialize)]
+struct MetadataFieldPayload {
+    field: String,
+    value: String,
+}
+
 impl IntroInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
         let (variant, rest) =
@@ -34,7 +61,8 @@ impl IntroInstruction {
 
         Ok(match variant {
             0 => {
-                let payload = StudentIntro::try_from_slice(rest).unwrap();
+                let payload = StudentIntro::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
 
                 IntroInstruction::CreateAccount {
                     name: payload.name,
@@ -42,19 +70,38 @@ impl IntroInstruction {
                 }
             }
             1 => {
-                let payload = StudentIntro::try_from_slice(rest).unwrap();
+                let payload = StudentIntro::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
                 IntroInstruction::UpdateAccount {
                     name: payload.name,
                     message: payload.message,
                 }
             }
             2 => {
-                let payload = ReplyPayload::try_from_slice(rest).unwrap();
+                let payload = ReplyPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
                 IntroInstruction::Reply {
                     reply: payload.reply,
                 }
             }
             3 => Self::InitializeMint,
+            4 => {
+                let payload = MetadataPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                IntroInstruction::InitializeMetadataMint {
+                    name: payload.name,
+                    symbol: payload.symbol,
+                    uri: payload.uri,
+                }
+            }
+            5 => {
+                let payload = MetadataFieldPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                IntroInstruction::UpdateMintMetadata {
+                    field: payload.field,
+                    value: payload.value,
+                }
+            }
             _ => return Err(ProgramError::InvalidInstructionData),
         })
     }
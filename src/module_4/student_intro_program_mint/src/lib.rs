@@ -1,5 +1,7 @@
+#[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
 pub mod error;
 pub mod instruction;
 pub mod processor;
 pub mod state;
+pub mod token_metadata;
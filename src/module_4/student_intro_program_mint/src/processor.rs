@@ -1,6 +1,10 @@
 use crate::error::StudentIntroError;
 use crate::instruction::IntroInstruction;
-use crate::state::{Reply, ReplyCounter, StudentInfo};
+use crate::state::{
+    Reply, ReplyCounter, StudentInfo, COUNTER_DISCRIMINATOR,
+    REPLY_DISCRIMINATOR, STUDENT_INFO_DISCRIMINATOR,
+};
+use crate::token_metadata::{self, metadata_len, Field, MINT_WITH_POINTER_LEN};
 use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
@@ -8,15 +12,18 @@ use solana_program::{
     entrypoint::ProgramResult,
     msg,
     native_token::LAMPORTS_PER_SOL,
-    program::invoke_signed,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     program_pack::IsInitialized,
     pubkey::Pubkey,
     system_instruction,
     sysvar::{rent::Rent, Sysvar},
 };
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use spl_token::{instruction::initialize_mint, ID as TOKEN_PROGRAM_ID};
+use spl_token_2022::{
+    instruction::initialize_mint2, ID as TOKEN_2022_PROGRAM_ID,
+};
 use std::convert::TryInto;
 
 pub fn process_instruction(
@@ -40,6 +47,15 @@ pub fn process_instruction(
         IntroInstruction::InitializeMint => {
             initialize_token_mint(program_id, accounts)
         }
+        IntroInstruction::InitializeMetadataMint {
+            name,
+            symbol,
+            uri,
+        } => initialize_metadata_mint(program_id, accounts, name, symbol, uri),
+        IntroInstruction::UpdateMintMetadata {
+            field,
+            value,
+        } => update_mint_metadata(program_id, accounts, field, value),
     }
 }
 
@@ -113,6 +129,171 @@ pub fn initialize_token_mint(
     Ok(())
 }
 
+pub fn initialize_metadata_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+    symbol: String,
+    uri: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    let (mint_pda, mint_bump) =
+        Pubkey::find_program_address(&[b"metadata_mint"], program_id);
+    let (mint_auth_pda, mint_auth_bump) =
+        Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    msg!("Token mint: {:?}", mint_pda);
+    msg!("Mint authority: {:?}", mint_auth_pda);
+
+    if mint_pda != *token_mint.key {
+        msg!("Incorrect token mint account");
+        return Err(StudentIntroError::IncorrectAccount.into());
+    }
+
+    if *token_program.key != TOKEN_2022_PROGRAM_ID {
+        msg!("Incorrect token program");
+        return Err(StudentIntroError::IncorrectAccount.into());
+    }
+
+    if *mint_auth.key != mint_auth_pda {
+        msg!("Incorrect mint auth account");
+        return Err(StudentIntroError::IncorrectAccount.into());
+    }
+
+    // Token-2022 grows the account to fit the metadata but does not pay for
+    // it, so the rent for both is deposited up front.
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(
+        MINT_WITH_POINTER_LEN + metadata_len(&name, &symbol, &uri),
+    );
+
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            token_mint.key,
+            rent_lamports,
+            MINT_WITH_POINTER_LEN as u64,
+            token_program.key,
+        ),
+        &[initializer.clone(), token_mint.clone(), system_program.clone()],
+        &[&[b"metadata_mint", &[mint_bump]]],
+    )?;
+
+    msg!("Created token mint account");
+
+    invoke(
+        &token_metadata::initialize_metadata_pointer(
+            token_mint.key,
+            Some(initializer.key),
+            Some(token_mint.key),
+        ),
+        &[token_mint.clone(), token_program.clone()],
+    )?;
+
+    invoke(
+        &initialize_mint2(
+            token_program.key,
+            token_mint.key,
+            mint_auth.key,
+            Option::None,
+            9,
+        )?,
+        &[token_mint.clone(), token_program.clone()],
+    )?;
+
+    msg!("Initialized token mint");
+
+    invoke_signed(
+        &token_metadata::initialize(
+            token_mint.key,
+            initializer.key,
+            mint_auth.key,
+            &name,
+            &symbol,
+            &uri,
+        )?,
+        &[token_mint.clone(), mint_auth.clone(), token_program.clone()],
+        &[&[b"token_auth", &[mint_auth_bump]]],
+    )?;
+
+    msg!("Initialized token metadata");
+
+    Ok(())
+}
+
+pub fn update_mint_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    field: String,
+    value: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    let (mint_pda, _mint_bump) =
+        Pubkey::find_program_address(&[b"metadata_mint"], program_id);
+
+    if mint_pda != *token_mint.key || *token_mint.owner != TOKEN_2022_PROGRAM_ID
+    {
+        msg!("Incorrect token mint account");
+        return Err(StudentIntroError::IncorrectAccount.into());
+    }
+
+    if *token_program.key != TOKEN_2022_PROGRAM_ID {
+        msg!("Incorrect token program");
+        return Err(StudentIntroError::IncorrectAccount.into());
+    }
+
+    let update_authority =
+        token_metadata::update_authority(&token_mint.data.borrow());
+    if !authority.is_signer || update_authority != Some(*authority.key) {
+        msg!("Signer is not the metadata update authority");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Covers the field being added as a new key, the largest it can grow.
+    let rent = Rent::get()?;
+    let grown_len =
+        token_mint.data_len() + (4 + field.len()) + (4 + value.len());
+    let top_up =
+        rent.minimum_balance(grown_len).saturating_sub(token_mint.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(
+                authority.key,
+                token_mint.key,
+                top_up,
+            ),
+            &[authority.clone(), token_mint.clone(), system_program.clone()],
+        )?;
+    }
+
+    invoke(
+        &token_metadata::update_field(
+            token_mint.key,
+            authority.key,
+            &Field::from(field.as_str()),
+            &value,
+        )?,
+        &[token_mint.clone(), authority.clone(), token_program.clone()],
+    )?;
+
+    msg!("Updated token metadata field {}", field);
+
+    Ok(())
+}
+
 pub fn add_student_intro(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -136,9 +317,10 @@ pub fn add_student_intro(
     let reply_counter = next_account_info(account_info_iter)?;
     let user_account = next_account_info(account_info_iter)?;
 
-    let (mint_pda, mint_bump) =
-        Pubkey::find_program_address(&[b"token_mint"], program_id);
-    let (mint_auth_pda, _mint_auth_bump) =
+    // Rewards come from the metadata mint so wallets can name the token.
+    let (mint_pda, _mint_bump) =
+        Pubkey::find_program_address(&[b"metadata_mint"], program_id);
+    let (mint_auth_pda, mint_auth_bump) =
         Pubkey::find_program_address(&[b"token_auth"], program_id);
 
     if *token_mint.key != mint_pda {
@@ -152,13 +334,17 @@ pub fn add_student_intro(
     }
 
     if *user_ata.key
-        != get_associated_token_address(initializer.key, token_mint.key)
+        != get_associated_token_address_with_program_id(
+            initializer.key,
+            token_mint.key,
+            &TOKEN_2022_PROGRAM_ID,
+        )
     {
         msg!("Incorrect token mint");
         return Err(StudentIntroError::IncorrectAccount.into());
     }
 
-    if *token_program.key != TOKEN_PROGRAM_ID {
+    if *token_program.key != TOKEN_2022_PROGRAM_ID {
         msg!("Incorrect token program");
         return Err(StudentIntroError::IncorrectAccount.into());
     }
@@ -174,7 +360,7 @@ pub fn add_student_intro(
     msg!("Minting 10 tokens to User associated token account");
     invoke_signed(
         // Instruction
-        &spl_token::instruction::mint_to(
+        &spl_token_2022::instruction::mint_to(
             token_program.key,
             token_mint.key,
             user_ata.key,
@@ -185,12 +371,12 @@ pub fn add_student_intro(
         // Account_infos
         &[token_mint.clone(), user_ata.clone(), mint_auth.clone()],
         // Seeds
-        &[&[b"token_mint", &[mint_bump]]],
+        &[&[b"token_auth", &[mint_auth_bump]]],
     )?;
 
     msg!("Tokens minted");
 
-    let studentinfo_discriminator = "studentinfo";
+    let studentinfo_discriminator = STUDENT_INFO_DISCRIMINATOR;
     let total_len: usize = (4 + studentinfo_discriminator.len())
         + 1
         + (4 + name.len())
@@ -232,7 +418,7 @@ pub fn add_student_intro(
     account_data.is_initialized = true;
     account_data.serialize(&mut &mut user_account.data.borrow_mut()[..])?;
 
-    let counter_discriminator = "counter";
+    let counter_discriminator = COUNTER_DISCRIMINATOR;
     let counter_len: usize = (4 + counter_discriminator.len()) + 1 + 1;
 
     let rent = Rent::get()?;
@@ -347,7 +533,7 @@ pub fn add_reply(
         try_from_slice_unchecked::<ReplyCounter>(&reply_counter.data.borrow())
             .unwrap();
 
-    let reply_discriminator = "reply";
+    let reply_discriminator = REPLY_DISCRIMINATOR;
     let account_len: usize =
         (4 + reply_discriminator.len()) + 1 + 32 + (4 + reply.len());
 
@@ -405,3 +591,263 @@ pub fn add_reply(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token_metadata::{
+        METADATA_POINTER_EXTENSION, METADATA_POINTER_LEN,
+        TOKEN_METADATA_EXTENSION,
+    };
+    use solana_program::{
+        entrypoint::SUCCESS, instruction::Instruction, program_pack::Pack,
+        program_stubs, system_program,
+    };
+    use spl_token_2022::state::Account;
+    use std::sync::{Arc, Mutex};
+
+    /// Cross-program invocations made by the processor, with the PDAs that
+    /// signed them.
+    type Invocations = Arc<Mutex<Vec<(Instruction, Vec<Pubkey>)>>>;
+
+    // The syscall stubs are global, so tests using them run one at a time.
+    static STUBS: Mutex<()> = Mutex::new(());
+
+    struct Stubs {
+        program_id: Pubkey,
+        invocations: Invocations,
+    }
+
+    impl program_stubs::SyscallStubs for Stubs {
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            _account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let signers = signers_seeds
+                .iter()
+                .map(|seeds| {
+                    Pubkey::create_program_address(seeds, &self.program_id)
+                        .unwrap()
+                })
+                .collect();
+            self.invocations
+                .lock()
+                .unwrap()
+                .push((instruction.clone(), signers));
+            Ok(())
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+    }
+
+    fn with_stubs<F: FnOnce(&Pubkey, &Invocations)>(test: F) {
+        let _guard = STUBS.lock().unwrap_or_else(|e| e.into_inner());
+        let program_id = Pubkey::new_unique();
+        let invocations = Invocations::default();
+        program_stubs::set_syscall_stubs(Box::new(Stubs {
+            program_id,
+            invocations: invocations.clone(),
+        }));
+        test(&program_id, &invocations);
+    }
+
+    struct TestAccount {
+        key: Pubkey,
+        is_signer: bool,
+        lamports: u64,
+        data: Vec<u8>,
+        owner: Pubkey,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey) -> Self {
+            Self {
+                key,
+                is_signer: false,
+                lamports: LAMPORTS_PER_SOL,
+                data: Vec::new(),
+                owner: system_program::id(),
+            }
+        }
+
+        fn signer(key: Pubkey) -> Self {
+            Self {
+                is_signer: true,
+                ..Self::new(key)
+            }
+        }
+
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                self.is_signer,
+                true,
+                &mut self.lamports,
+                &mut self.data,
+                &self.owner,
+                false,
+                0,
+            )
+        }
+    }
+
+    /// Metadata mint data with the pointer authority and the metadata's
+    /// update authority set to different keys.
+    fn metadata_mint_data(
+        pointer_authority: &Pubkey,
+        update_authority: &Pubkey,
+    ) -> Vec<u8> {
+        let mut data = vec![0; Account::LEN + 1];
+        data.extend_from_slice(&METADATA_POINTER_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&(METADATA_POINTER_LEN as u16).to_le_bytes());
+        data.extend_from_slice(pointer_authority.as_ref());
+        data.extend_from_slice(&[0; 32]);
+
+        let mut metadata = update_authority.to_bytes().to_vec();
+        metadata.extend_from_slice(&[0; 32]);
+        ("Course", "CRS", "u", Vec::<(String, String)>::new())
+            .serialize(&mut metadata)
+            .unwrap();
+        data.extend_from_slice(&TOKEN_METADATA_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&(metadata.len() as u16).to_le_bytes());
+        data.extend_from_slice(&metadata);
+        data
+    }
+
+    fn update_metadata(
+        program_id: &Pubkey,
+        signer: &Pubkey,
+        mint_data: Vec<u8>,
+    ) -> ProgramResult {
+        let mut accounts = [
+            TestAccount::signer(*signer),
+            TestAccount {
+                data: mint_data,
+                owner: TOKEN_2022_PROGRAM_ID,
+                ..TestAccount::new(
+                    Pubkey::find_program_address(
+                        &[b"metadata_mint"],
+                        program_id,
+                    )
+                    .0,
+                )
+            },
+            TestAccount::new(system_program::id()),
+            TestAccount::new(TOKEN_2022_PROGRAM_ID),
+        ];
+        let infos: Vec<_> =
+            accounts.iter_mut().map(TestAccount::info).collect();
+
+        update_mint_metadata(program_id, &infos, "uri".into(), "v".into())
+    }
+
+    #[test]
+    fn only_the_metadata_update_authority_updates_metadata() {
+        with_stubs(|program_id, invocations| {
+            let pointer_authority = Pubkey::new_unique();
+            let update_authority = Pubkey::new_unique();
+            let data =
+                metadata_mint_data(&pointer_authority, &update_authority);
+
+            assert_eq!(
+                update_metadata(program_id, &pointer_authority, data.clone()),
+                Err(ProgramError::MissingRequiredSignature)
+            );
+            assert!(invocations.lock().unwrap().is_empty());
+
+            update_metadata(program_id, &update_authority, data).unwrap();
+            let invocations = invocations.lock().unwrap();
+            let (update, signers) = invocations.last().unwrap();
+            assert_eq!(
+                *update,
+                token_metadata::update_field(
+                    &Pubkey::find_program_address(
+                        &[b"metadata_mint"],
+                        program_id
+                    )
+                    .0,
+                    &update_authority,
+                    &Field::Uri,
+                    "v",
+                )
+                .unwrap()
+            );
+            assert!(signers.is_empty());
+        });
+    }
+
+    #[test]
+    fn rewards_are_minted_from_the_metadata_mint() {
+        with_stubs(|program_id, invocations| {
+            let initializer = Pubkey::new_unique();
+            let metadata_mint =
+                Pubkey::find_program_address(&[b"metadata_mint"], program_id).0;
+            let mint_auth =
+                Pubkey::find_program_address(&[b"token_auth"], program_id).0;
+            let intro = Pubkey::find_program_address(
+                &[initializer.as_ref()],
+                program_id,
+            )
+            .0;
+            let counter = Pubkey::find_program_address(
+                &[intro.as_ref(), b"reply"],
+                program_id,
+            )
+            .0;
+            let add = |token_mint: Pubkey| {
+                let mut accounts = [
+                    TestAccount::signer(initializer),
+                    TestAccount::new(intro),
+                    TestAccount::new(counter),
+                    TestAccount::new(token_mint),
+                    TestAccount::new(mint_auth),
+                    TestAccount::new(
+                        get_associated_token_address_with_program_id(
+                            &initializer,
+                            &token_mint,
+                            &TOKEN_2022_PROGRAM_ID,
+                        ),
+                    ),
+                    TestAccount::new(system_program::id()),
+                    TestAccount::new(TOKEN_2022_PROGRAM_ID),
+                    TestAccount {
+                        data: vec![0; 4 + COUNTER_DISCRIMINATOR.len() + 2],
+                        ..TestAccount::new(counter)
+                    },
+                    TestAccount {
+                        data: vec![0; 1000],
+                        ..TestAccount::new(intro)
+                    },
+                ];
+                let infos: Vec<_> =
+                    accounts.iter_mut().map(TestAccount::info).collect();
+                add_student_intro(
+                    program_id,
+                    &infos,
+                    "Ada".into(),
+                    "hello".into(),
+                )
+            };
+
+            let legacy_mint =
+                Pubkey::find_program_address(&[b"token_mint"], program_id).0;
+            assert_eq!(
+                add(legacy_mint),
+                Err(StudentIntroError::IncorrectAccount.into())
+            );
+
+            add(metadata_mint).unwrap();
+            let invocations = invocations.lock().unwrap();
+            let (mint_to, signers) = &invocations[0];
+            assert_eq!(mint_to.program_id, TOKEN_2022_PROGRAM_ID);
+            assert_eq!(mint_to.accounts[0].pubkey, metadata_mint);
+            assert_eq!(mint_to.accounts[2].pubkey, mint_auth);
+            assert_eq!(*signers, [mint_auth]);
+        });
+    }
+}
@@ -4,14 +4,18 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
-#[derive(BorshSerialize, BorshDeserialize)]
+pub const STUDENT_INFO_DISCRIMINATOR: &str = "studentinfo";
+pub const COUNTER_DISCRIMINATOR: &str = "counter";
+pub const REPLY_DISCRIMINATOR: &str = "reply";
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct ReplyCounter {
     pub discriminator: String,
     pub is_initialized: bool,
     pub counter: u8,
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct Reply {
     pub discriminator: String,
     pub is_initialized: bool,
@@ -19,7 +23,7 @@ pub struct Reply {
     pub reply: String,
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct StudentInfo {
     pub is_initialized: bool,
     pub name: String,
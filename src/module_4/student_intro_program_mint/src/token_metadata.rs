@@ -0,0 +1,220 @@
+//! Token-2022 metadata pointer and token metadata instructions. The
+//! spl-token-2022 version this program is pinned to predates these
+//! extensions, so they are encoded by hand.
+
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token_2022::state::Account;
+
+/// Extension type of the metadata pointer in a mint's TLV data.
+pub const METADATA_POINTER_EXTENSION: u16 = 18;
+/// Extension type of the token metadata stored in the mint itself.
+pub const TOKEN_METADATA_EXTENSION: u16 = 19;
+/// Size of the metadata pointer, its authority and metadata address.
+pub const METADATA_POINTER_LEN: usize = 64;
+/// Bytes each extension takes besides its value, its type and length.
+pub const EXTENSION_HEADER_LEN: usize = 4;
+/// Size of a mint holding only the metadata pointer.
+pub const MINT_WITH_POINTER_LEN: usize =
+    Account::LEN + 1 + EXTENSION_HEADER_LEN + METADATA_POINTER_LEN;
+
+const METADATA_POINTER_INSTRUCTION: u8 = 39;
+const METADATA_POINTER_INITIALIZE: u8 = 0;
+
+// First 8 bytes of the SHA-256 of the interface's instruction names, such
+// as `spl_token_metadata_interface:initialize_account`.
+const INITIALIZE_DISCRIMINATOR: [u8; 8] = [210, 225, 30, 162, 88, 184, 77, 141];
+const UPDATE_FIELD_DISCRIMINATOR: [u8; 8] =
+    [221, 233, 49, 45, 181, 202, 220, 200];
+
+/// A metadata field, the known ones or any additional key.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize)]
+pub enum Field {
+    Name,
+    Symbol,
+    Uri,
+    Key(String),
+}
+
+impl From<&str> for Field {
+    fn from(s: &str) -> Self {
+        match s {
+            "name" => Field::Name,
+            "symbol" => Field::Symbol,
+            "uri" => Field::Uri,
+            key => Field::Key(key.to_string()),
+        }
+    }
+}
+
+/// Size of the stored metadata with its extension header, before any
+/// additional fields are added.
+pub fn metadata_len(name: &str, symbol: &str, uri: &str) -> usize {
+    EXTENSION_HEADER_LEN
+        + 64
+        + (4 + name.len())
+        + (4 + symbol.len())
+        + (4 + uri.len())
+        + 4
+}
+
+/// Points `mint` at metadata stored in `metadata_address`. Must come before
+/// the mint is initialized, in an account sized for the pointer.
+pub fn initialize_metadata_pointer(
+    mint: &Pubkey,
+    authority: Option<&Pubkey>,
+    metadata_address: Option<&Pubkey>,
+) -> Instruction {
+    let mut data =
+        vec![METADATA_POINTER_INSTRUCTION, METADATA_POINTER_INITIALIZE];
+    data.extend_from_slice(authority.copied().unwrap_or_default().as_ref());
+    data.extend_from_slice(
+        metadata_address.copied().unwrap_or_default().as_ref(),
+    );
+
+    Instruction::new_with_bytes(
+        spl_token_2022::id(),
+        &data,
+        vec![AccountMeta::new(*mint, false)],
+    )
+}
+
+/// Stores the metadata in `mint` itself, signed by its mint authority.
+pub fn initialize(
+    mint: &Pubkey,
+    update_authority: &Pubkey,
+    mint_authority: &Pubkey,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+) -> Result<Instruction, ProgramError> {
+    let mut data = INITIALIZE_DISCRIMINATOR.to_vec();
+    (name, symbol, uri).serialize(&mut data)?;
+
+    Ok(Instruction::new_with_bytes(
+        spl_token_2022::id(),
+        &data,
+        vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*update_authority, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+        ],
+    ))
+}
+
+/// Sets one field of the metadata stored in `mint`, adding additional keys
+/// that are missing.
+pub fn update_field(
+    mint: &Pubkey,
+    update_authority: &Pubkey,
+    field: &Field,
+    value: &str,
+) -> Result<Instruction, ProgramError> {
+    let mut data = UPDATE_FIELD_DISCRIMINATOR.to_vec();
+    (field, value).serialize(&mut data)?;
+
+    Ok(Instruction::new_with_bytes(
+        spl_token_2022::id(),
+        &data,
+        vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*update_authority, true),
+        ],
+    ))
+}
+
+/// Value of `extension` in a Token-2022 mint's TLV data, `None` when the
+/// mint does not have it.
+pub fn find_extension(data: &[u8], extension: u16) -> Option<&[u8]> {
+    let mut tlv = data.get(Account::LEN + 1..)?;
+    while tlv.len() >= EXTENSION_HEADER_LEN {
+        let extension_type = u16::from_le_bytes([tlv[0], tlv[1]]);
+        let len = usize::from(u16::from_le_bytes([tlv[2], tlv[3]]));
+        let value =
+            tlv.get(EXTENSION_HEADER_LEN..EXTENSION_HEADER_LEN + len)?;
+        if extension_type == extension {
+            return Some(value);
+        }
+        if extension_type == 0 {
+            return None;
+        }
+        tlv = &tlv[EXTENSION_HEADER_LEN + len..];
+    }
+    None
+}
+
+/// Update authority of the token metadata stored in a Token-2022 mint,
+/// `None` when the mint has no metadata or its authority was removed.
+pub fn update_authority(data: &[u8]) -> Option<Pubkey> {
+    let metadata = find_extension(data, TOKEN_METADATA_EXTENSION)?;
+    Pubkey::try_from(metadata.get(..32)?)
+        .ok()
+        .filter(|authority| *authority != Pubkey::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mint_with_metadata(update_authority: &Pubkey) -> Vec<u8> {
+        let mut data = vec![0; Account::LEN + 1];
+        data.extend_from_slice(&METADATA_POINTER_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&(METADATA_POINTER_LEN as u16).to_le_bytes());
+        data.extend_from_slice(&[7; METADATA_POINTER_LEN]);
+        assert_eq!(data.len(), MINT_WITH_POINTER_LEN);
+
+        let mut metadata = update_authority.to_bytes().to_vec();
+        metadata.extend_from_slice(&[0; 32]);
+        ("Course", "CRS", "u", Vec::<(String, String)>::new())
+            .serialize(&mut metadata)
+            .unwrap();
+        data.extend_from_slice(&TOKEN_METADATA_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&(metadata.len() as u16).to_le_bytes());
+        data.extend_from_slice(&metadata);
+        assert_eq!(
+            data.len(),
+            MINT_WITH_POINTER_LEN + metadata_len("Course", "CRS", "u")
+        );
+        data
+    }
+
+    #[test]
+    fn reads_the_metadata_update_authority() {
+        let authority = Pubkey::new_unique();
+        let mut data = mint_with_metadata(&authority);
+
+        assert_eq!(update_authority(&data), Some(authority));
+        assert_eq!(
+            find_extension(&data, METADATA_POINTER_EXTENSION),
+            Some(&[7; METADATA_POINTER_LEN][..])
+        );
+
+        // The pointer authority is not the metadata's.
+        data.truncate(MINT_WITH_POINTER_LEN);
+        assert_eq!(update_authority(&data), None);
+        assert_eq!(
+            update_authority(&mint_with_metadata(&Pubkey::default())),
+            None
+        );
+        assert_eq!(update_authority(&[0; 82]), None);
+    }
+
+    #[test]
+    fn encodes_update_field() {
+        let instruction = update_field(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Field::from("uri"),
+            "u",
+        )
+        .unwrap();
+        assert_eq!(instruction.data[..8], UPDATE_FIELD_DISCRIMINATOR);
+        assert_eq!(instruction.data[8..], [2, 1, 0, 0, 0, b'u']);
+    }
+}